@@ -155,6 +155,7 @@ pub fn nvn_struct(attrs: TokenStream, input: TokenStream) -> TokenStream {
 
     let usr_ident = input.ident.clone();
     let usr_vis = input.vis.clone();
+    let usr_struct_attrs = input.attrs.iter();
 
     let opaque_size = usr_attrs.opaque_size.clone();
 
@@ -194,6 +195,9 @@ pub fn nvn_struct(attrs: TokenStream, input: TokenStream) -> TokenStream {
     let impls = impls.iter();
 
     let new_struct = quote!(
+        #(
+            #usr_struct_attrs
+        )*
         #[repr(C)]
         #usr_vis struct #usr_ident {
             _opaque: [u8; #opaque_size]
@@ -0,0 +1,83 @@
+use crate::managed::{BuildError, CommandMemoryError, CopyError, DeviceError, InitializeError, MemPoolError, QueueError, UploadError};
+use thiserror::Error;
+
+/// Every error the managed layer reports, so callers can `?` through it. The raw bindings return
+/// what the driver does, `InitializeError::check` turns their `initialize` results into errors.
+/// Each variant wraps the error of the layer it came from, which `source()` returns for the
+/// details.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Object initialization failed")]
+    Initialize(#[from] InitializeError),
+    #[error("Invalid builder settings")]
+    Build(#[from] BuildError),
+    #[error("Device setup failed")]
+    Device(#[from] DeviceError),
+    #[error("GPU queue reported an error")]
+    Queue(#[from] QueueError),
+    #[error("GPU memory allocation failed")]
+    Allocation(#[from] MemPoolError),
+    #[error("Command buffer ran out of memory")]
+    CommandMemory(#[from] CommandMemoryError),
+    #[error("Invalid copy")]
+    Copy(#[from] CopyError),
+    #[error("Upload failed")]
+    Upload(#[from] UploadError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn wraps_initialize_error() {
+        let err = Error::from(InitializeError("Fence"));
+        assert!(matches!(err, Error::Initialize(_)));
+
+        let source = err.source().and_then(|source| source.downcast_ref::<InitializeError>()).unwrap();
+        assert_eq!(source.object(), "Fence");
+    }
+
+    #[test]
+    fn checks_raw_initialize_result() {
+        assert!(InitializeError::check(true, "Sampler").is_ok());
+
+        let err = Error::from(InitializeError::check(false, "Sampler").unwrap_err());
+        assert!(matches!(err, Error::Initialize(ref init) if init.object() == "Sampler"));
+    }
+
+    #[test]
+    fn wraps_build_error() {
+        let err = Error::from(BuildError::MissingDevice);
+        assert!(matches!(err, Error::Build(BuildError::MissingDevice)));
+        assert_eq!(err.source().unwrap().to_string(), "`device` was not set");
+    }
+
+    #[test]
+    fn chains_initialize_error_through_build_error() {
+        let err = Error::from(BuildError::from(InitializeError("Queue")));
+
+        let build = err.source().unwrap();
+        assert!(build.is::<BuildError>());
+        // `BuildError::Initialize` is transparent and displays as the error it wraps
+        assert_eq!(build.to_string(), "Failed to initialize Queue object");
+    }
+
+    #[test]
+    fn wraps_device_error() {
+        let err = Error::from(DeviceError::UnresolvedProc("nvnDeviceInitialize"));
+        assert!(matches!(err, Error::Device(DeviceError::UnresolvedProc(_))));
+        assert_eq!(err.source().and_then(|source| source.downcast_ref::<DeviceError>()), Some(&DeviceError::UnresolvedProc("nvnDeviceInitialize")));
+    }
+
+    #[test]
+    fn converts_through_question_mark() {
+        fn fails() -> Result<(), Error> {
+            Err(CommandMemoryError::OutOfControlMemory(0x1000))?;
+            Ok(())
+        }
+
+        assert!(matches!(fails(), Err(Error::CommandMemory(CommandMemoryError::OutOfControlMemory(0x1000)))));
+    }
+}
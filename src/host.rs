@@ -0,0 +1,62 @@
+//! Stand-ins for what the Switch SDK provides, so the crate builds on a regular host.
+
+use libc::{c_char, c_void};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub type NativeWindowHandle = *mut c_void;
+
+pub type BootstrapLoader = unsafe extern "C" fn(ident: *const c_char) -> *const c_void;
+
+static LOADER: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the function procs are resolved through in place of `nvnBootstrapLoader`, like a mock
+/// driver. Without one every proc resolves to null.
+pub fn set_bootstrap_loader(loader: Option<BootstrapLoader>) {
+    LOADER.store(loader.map_or(0, |loader| loader as usize), Ordering::SeqCst);
+}
+
+#[allow(non_snake_case)]
+pub(crate) unsafe fn nvnBootstrapLoader(ident: *const c_char) -> *const c_void {
+    match LOADER.load(Ordering::SeqCst) {
+        0 => 0 as _,
+        loader => std::mem::transmute::<usize, BootstrapLoader>(loader)(ident)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    static QUEUE_INITIALIZE: u8 = 0;
+
+    unsafe extern "C" fn loader(ident: *const c_char) -> *const c_void {
+        match CStr::from_ptr(ident).to_bytes() {
+            b"nvnQueueInitialize" => &QUEUE_INITIALIZE as *const u8 as _,
+            _ => 0 as _
+        }
+    }
+
+    // the loader is global, so everything touching it stays in one test
+    #[test]
+    fn procs_resolve_through_the_bootstrap_loader() {
+        let expected = &QUEUE_INITIALIZE as *const u8 as *const c_void;
+        let ident = b"nvnQueueInitialize\0".as_ptr() as *const c_char;
+
+        unsafe {
+            assert!(nvnBootstrapLoader(ident).is_null());
+
+            set_bootstrap_loader(Some(loader));
+            assert_eq!(nvnBootstrapLoader(ident), expected);
+            assert!(nvnBootstrapLoader(b"nvnQueueFinalize\0".as_ptr() as _).is_null());
+        }
+
+        // without a device procs are looked up through the loader
+        assert_eq!(crate::nvn_resolver("nvnQueueInitialize\0"), (expected, false));
+
+        set_bootstrap_loader(None);
+        unsafe {
+            assert!(nvnBootstrapLoader(ident).is_null());
+        }
+    }
+}
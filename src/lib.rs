@@ -3,31 +3,173 @@
 use nvn_macro::*;
 use libc::*;
 use modular_bitfield::prelude::*;
+use bytemuck::{Pod, Zeroable};
 
 static mut DEVICE_HAS_INIT: bool = false;
+static mut PROC_DEVICE: *const Device = 0 as _;
 #[cfg(not(feature = "runtime"))]
-static mut GLOBAL_DEVICE: Device = Device::new();
+static mut GLOBAL_DEVICE: Option<managed::Device> = None;
 #[cfg(feature = "runtime")]
 static mut GLOBAL_DEVICE: Option<*mut Device> = None;
 
 pub mod managed;
+mod error;
+#[cfg(not(target_os = "switch"))]
+pub mod host;
 
+pub use error::Error;
+
+#[cfg(target_os = "switch")]
 pub use nn::vi::NativeWindowHandle as NativeWindowHandle;
+#[cfg(not(target_os = "switch"))]
+pub use host::NativeWindowHandle;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct CommandHandle(u64);
 
 #[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct TextureHandle(u64);
 
 #[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ImageHandle(u64);
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Pod, Zeroable)]
+pub struct BufferAddress(pub u64);
+
+impl BufferAddress {
+    pub const fn offset(self, offset: u64) -> Self {
+        BufferAddress(self.0 + offset)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BufferRange {
+    pub address: BufferAddress,
+    pub size: u64
+}
+
+/// Layout of a single draw read by `draw_arrays_indirect` and `multi_draw_arrays_indirect_count`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Pod, Zeroable)]
+pub struct DrawArraysIndirectData {
+    pub count: i32,
+    pub instance_count: i32,
+    pub first: i32,
+    pub base_instance: i32
+}
+
+/// Layout of a single draw read by `draw_elements_indirect` and `multi_draw_elements_indirect_count`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Pod, Zeroable)]
+pub struct DrawElementsIndirectData {
+    pub count: i32,
+    pub instance_count: i32,
+    pub first_index: i32,
+    pub base_vertex: i32,
+    pub base_instance: i32
+}
+
+/// Layout read by `dispatch_compute_indirect`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Pod, Zeroable)]
+pub struct DispatchComputeIndirectData {
+    pub groups_x: i32,
+    pub groups_y: i32,
+    pub groups_z: i32
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CopyRegion {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub width: i32,
+    pub height: i32,
+    pub depth: i32
+}
+
+impl CopyRegion {
+    pub const fn new(x: i32, y: i32, z: i32, width: i32, height: i32, depth: i32) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            width,
+            height,
+            depth
+        }
+    }
+
+    /// Region covering the whole base level of `texture`.
+    pub fn from_texture(texture: &Texture) -> Self {
+        Self::new(0, 0, 0, texture.get_width(), texture.get_height(), texture.get_depth())
+    }
+
+    /// Whether this region is non-empty and lies within a `width`x`height`x`depth` image.
+    pub fn fits(&self, width: i32, height: i32, depth: i32) -> bool {
+        let fits_axis = |offset: i32, size: i32, max: i32| {
+            offset >= 0 && size > 0 && offset.checked_add(size).is_some_and(|end| end <= max)
+        };
+
+        fits_axis(self.x, self.width, width)
+            && fits_axis(self.y, self.height, height)
+            && fits_axis(self.z, self.depth, depth)
+    }
+}
+
+/// Layout written by `report_counter`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Pod, Zeroable)]
+pub struct ReportValue {
+    pub counter: u64,
+    pub timestamp: u64
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MmuFaultInfo {
+    pub fault_address: u64,
+    pub access_type: MmuFaultAccessType
+}
+
+/// Details filled in by `nvnQueueGetError`. Only `mmu_fault` is meaningful, and only when the
+/// error is `QueueGetErrorResult::GpuErrorMmuFault`.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union QueueErrorInfo {
+    pub mmu_fault: MmuFaultInfo,
+    reserved: [i32; 16]
+}
+
+impl Default for QueueErrorInfo {
+    fn default() -> Self {
+        Self {
+            reserved: [0; 16]
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ShaderData {
+    pub data: BufferAddress,
+    pub control: *const u8
+}
+
+#[cfg(target_os = "switch")]
 extern "C" {
     fn nvnBootstrapLoader(ident: *const c_char) -> *const c_void;
 }
 
+#[cfg(not(target_os = "switch"))]
+use host::nvnBootstrapLoader;
+
 fn nvn_resolver(ident: &str) -> (*const c_void, bool) {
     unsafe {
         if !DEVICE_HAS_INIT {
@@ -41,38 +183,74 @@ fn nvn_resolver(ident: &str) -> (*const c_void, bool) {
                 if nvn_internal_nvnDeviceGetProcAddress_func_ptr.is_null() {
                     nvn_internal_nvnDeviceGetProcAddress_func_ptr = nvnBootstrapLoader(ident.as_ptr() as _) as _;
                 }
-                let ret = std::mem::transmute::<_, extern "C" fn(*const Device, *const c_char) -> *const c_void>(nvn_internal_nvnDeviceGetProcAddress_func_ptr)(global_device(), ident.as_ptr() as _);
+                let ret = std::mem::transmute::<_, extern "C" fn(*const Device, *const c_char) -> *const c_void>(nvn_internal_nvnDeviceGetProcAddress_func_ptr)(PROC_DEVICE, ident.as_ptr() as _);
                 (ret, true)
             } else {
-                (global_device().get_proc(ident.as_ptr() as _), true)
+                ((*PROC_DEVICE).get_proc(ident.as_ptr() as _), true)
             }
         }
     }
 }
 
-#[cfg(not(feature = "runtime"))]
-pub fn init() {
+/// Initializes `device` with procs resolved through the bootstrap loader, since nothing can be
+/// resolved through a device before one exists.
+pub(crate) fn bootstrap_device_init(device: &mut Device, builder: &DeviceBuilder) -> Result<bool, &'static str> {
     unsafe {
         nvnDeviceInitialize::resolve();
         nvnDeviceGetProcAddress::resolve();
+        if nvn_internal_nvnDeviceInitialize_func_ptr.is_null() {
+            return Err("nvnDeviceInitialize");
+        }
+        if nvn_internal_nvnDeviceGetProcAddress_func_ptr.is_null() {
+            return Err("nvnDeviceGetProcAddress");
+        }
         nvn_internal_nvnDeviceGetProcAddress_is_resolved = true;
         nvn_internal_nvnDeviceInitialize_is_resolved = true;
-        let mut builder = DeviceBuilder::new();
-        builder.set_defaults();
-        builder.set_flags(0);
-        DEVICE_HAS_INIT = GLOBAL_DEVICE.init(&builder);
+        let ret = device.init(builder);
         nvn_internal_nvnDeviceGetProcAddress_is_resolved = false;
         nvn_internal_nvnDeviceInitialize_is_resolved = false;
-        if DEVICE_HAS_INIT {
+        Ok(ret)
+    }
+}
+
+/// Makes `device` the one procs get resolved through, unless another device already is.
+pub(crate) fn register_proc_device(device: *const Device) {
+    unsafe {
+        if PROC_DEVICE.is_null() {
+            PROC_DEVICE = device;
+            DEVICE_HAS_INIT = true;
             DeviceBuilder::resolve();
             Device::resolve();
         }
     }
 }
 
+pub(crate) fn unregister_proc_device(device: *const Device) {
+    unsafe {
+        if PROC_DEVICE == device {
+            PROC_DEVICE = 0 as _;
+            DEVICE_HAS_INIT = false;
+        }
+    }
+}
+
+/// Creates a device with default settings and makes it the global device.
+#[cfg(not(feature = "runtime"))]
+pub fn init() -> Result<(), managed::DeviceError> {
+    let device = managed::Device::new().finish()?;
+    unsafe {
+        GLOBAL_DEVICE = Some(device);
+    }
+    Ok(())
+}
+
 #[cfg(not(feature = "runtime"))]
 pub fn global_device() -> &'static mut Device {
-    unsafe { &mut GLOBAL_DEVICE }
+    unsafe {
+        GLOBAL_DEVICE
+            .as_mut()
+            .expect("Global device not initialized!")
+    }
 }
 
 #[cfg(feature = "runtime")]
@@ -90,7 +268,7 @@ pub fn global_device() -> &'static mut Device {
 pub fn set_global_device(device: *mut Device) {
     unsafe {
         GLOBAL_DEVICE = Some(device);
-        DEVICE_HAS_INIT = true;
+        register_proc_device(device);
     }
 }
 
@@ -98,7 +276,7 @@ pub fn set_global_device(device: *mut Device) {
 pub struct DeviceBuilder {
     #[nvn_proc(fn nvnDeviceBuilderSetDefaults())]
     pub set_defaults: (),
-    #[nvn_proc(fn nvnDeviceBuilderSetFlags(flags: u32))]
+    #[nvn_proc(fn nvnDeviceBuilderSetFlags(flags: DeviceFlags))]
     pub set_flags: ()
 }
 
@@ -119,7 +297,9 @@ pub struct QueueBuilder {
     #[nvn_proc(fn nvnQueueBuilderSetQueueMemorySize(size: usize))]
     pub set_mem_size: (),
     #[nvn_proc(fn nvnQueueBuilderSetCommandFlushThreshold(size: usize))]
-    pub set_cmd_flush_threshold: ()
+    pub set_cmd_flush_threshold: (),
+    #[nvn_proc(fn nvnQueueBuilderSetQueueMemory(memory: *mut u8, size: usize))]
+    pub set_memory: ()
 }
 
 #[nvn_struct(0x3000, nvn_resolver)]
@@ -134,14 +314,34 @@ pub struct Device {
     pub set_name: (),
     #[nvn_proc(const fn nvnDeviceGetInteger(what: u32, out: *mut i32))]
     pub get_int: (),
-    #[nvn_proc(const fn nvnDeviceGetCurrentTimestampInNanoseconds())]
+    #[nvn_proc(const fn nvnDeviceGetCurrentTimestampInNanoseconds() -> u64)]
     pub get_time_nanos: (),
+    #[nvn_proc(const fn nvnDeviceGetTimestampInNanoseconds(value: *const ReportValue) -> u64)]
+    pub get_timestamp_nanos: (),
     #[nvn_proc(const fn nvnDeviceGetTextureHandle(texture_id: i32, sampler_id: i32) -> TextureHandle)]
     pub get_texture_handle: (),
     #[nvn_proc(const fn nvnDeviceGetTexelFetchHandle(texture_id: i32) -> TextureHandle)]
     pub get_texel_handle: (),
     #[nvn_proc(const fn nvnDeviceGetImageHandle(image_id: i32) -> ImageHandle)]
-    pub get_image_handle: ()
+    pub get_image_handle: (),
+    #[nvn_proc(fn nvnDeviceSetWindowOriginMode(mode: WindowOriginMode))]
+    pub set_window_origin_mode: (),
+    #[nvn_proc(fn nvnDeviceSetDepthMode(mode: DepthMode))]
+    pub set_depth_mode: (),
+    #[nvn_proc(const fn nvnDeviceGetWindowOriginMode() -> WindowOriginMode)]
+    pub get_window_origin_mode: (),
+    #[nvn_proc(const fn nvnDeviceGetDepthMode() -> DepthMode)]
+    pub get_depth_mode: (),
+    #[nvn_proc(fn nvnDeviceApplyDeferredFinalizes(age: i32))]
+    pub apply_deferred_finalizes: ()
+}
+
+impl Device {
+    pub fn get_info(&self, info: DeviceInfo) -> i32 {
+        let mut out = 0;
+        self.get_int(info as u32, &mut out);
+        out
+    }
 }
 
 #[nvn_struct(0x2000, nvn_resolver)]
@@ -154,14 +354,131 @@ pub struct Queue {
     pub submit_commands: (),
     #[nvn_proc(fn nvnQueueFlush())]
     pub flush: (),
+    #[nvn_proc(fn nvnQueueFenceSync(sync: *mut SyncObject, condition: SyncCondition, flags: SyncFlags))]
+    pub fence_sync: (),
+    #[nvn_proc(fn nvnQueueWaitSync(sync: *const SyncObject) -> bool)]
+    pub wait_sync: (),
+    #[nvn_proc(fn nvnQueueFinish())]
+    pub finish: (),
+    #[nvn_proc(fn nvnQueuePresentTexture(window: *mut Window, texture_index: i32))]
+    pub present_texture: (),
+    #[nvn_proc(fn nvnQueueGetError(info: *mut QueueErrorInfo) -> QueueGetErrorResult)]
+    pub get_error: (),
+}
+
+#[nvn_struct(64, nvn_resolver)]
+pub struct WindowBuilder {
+    #[nvn_proc(fn nvnWindowBuilderSetDevice(device: *const Device) -> *const WindowBuilder)]
+    pub set_device: (),
+    #[nvn_proc(fn nvnWindowBuilderSetDefaults() -> *const WindowBuilder)]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnWindowBuilderSetNativeWindow(window: NativeWindowHandle) -> *const WindowBuilder)]
+    pub set_native_window: (),
+    #[nvn_proc(fn nvnWindowBuilderSetTextures(count: i32, textures: *const *const Texture) -> *const WindowBuilder)]
+    pub set_textures: (),
+    #[nvn_proc(fn nvnWindowBuilderSetPresentInterval(interval: i32) -> *const WindowBuilder)]
+    pub set_present_interval: ()
+}
+
+#[nvn_struct(384, nvn_resolver)]
+pub struct Window {
+    #[nvn_proc(fn nvnWindowInitialize(builder: *const WindowBuilder) -> bool)]
+    pub initialize: (),
+    #[nvn_proc(fn nvnWindowFinalize())]
+    pub finalize: (),
+    #[nvn_proc(fn nvnWindowAcquireTexture(sync: *mut SyncObject, texture_index: *mut i32) -> WindowAcquireTextureResult)]
+    pub acquire_texture: (),
+    #[nvn_proc(fn nvnWindowSetPresentInterval(interval: i32))]
+    pub set_present_interval: (),
+    #[nvn_proc(const fn nvnWindowGetPresentInterval() -> i32)]
+    pub get_present_interval: ()
+}
+
+/// `NVNsync`, named so it doesn't shadow `std::marker::Sync` for glob imports of the crate.
+#[nvn_struct(64, nvn_resolver)]
+pub struct SyncObject {
+    #[nvn_proc(fn nvnSyncInitialize(device: *const Device) -> bool)]
+    pub initialize: (),
+    #[nvn_proc(fn nvnSyncFinalize())]
+    pub finalize: (),
+    #[nvn_proc(fn nvnSyncSetDebugLabel(label: *const c_char))]
+    pub set_name: (),
+    #[nvn_proc(const fn nvnSyncWait(timeout_ns: u64) -> SyncWaitResult)]
+    pub wait: ()
 }
 
 #[nvn_struct(192, nvn_resolver)]
 pub struct Texture {
-    #[nvn_proc(fn nvnTextureGetWidth() -> i32)]
+    #[nvn_proc(fn nvnTextureFinalize())]
+    pub finalize: (),
+    #[nvn_proc(const fn nvnTextureGetWidth() -> i32)]
     pub get_width: (),
-    #[nvn_proc(fn nvnTextureGetHeight() -> i32)]
+    #[nvn_proc(const fn nvnTextureGetHeight() -> i32)]
     pub get_height: (),
+    #[nvn_proc(const fn nvnTextureGetDepth() -> i32)]
+    pub get_depth: (),
+    #[nvn_proc(const fn nvnTextureGetLevels() -> i32)]
+    pub get_levels: (),
+    #[nvn_proc(const fn nvnTextureGetFormat() -> Format)]
+    pub get_format: (),
+    #[nvn_proc(const fn nvnTextureGetTarget() -> TextureTarget)]
+    pub get_target: (),
+}
+
+#[nvn_struct(40, nvn_resolver)]
+pub struct TextureView {
+    #[nvn_proc(fn nvnTextureViewSetDefaults() -> *const TextureView)]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnTextureViewSetLevels(base_level: i32, num_levels: i32) -> *const TextureView)]
+    pub set_levels: (),
+    #[nvn_proc(fn nvnTextureViewSetLayers(min_layer: i32, num_layers: i32) -> *const TextureView)]
+    pub set_layers: (),
+    #[nvn_proc(fn nvnTextureViewSetFormat(format: Format) -> *const TextureView)]
+    pub set_format: (),
+    #[nvn_proc(const fn nvnTextureViewGetLevels(base_level: *mut i32, num_levels: *mut i32) -> bool)]
+    pub get_levels: (),
+    #[nvn_proc(const fn nvnTextureViewGetLayers(min_layer: *mut i32, num_layers: *mut i32) -> bool)]
+    pub get_layers: ()
+}
+
+#[nvn_struct(64, nvn_resolver)]
+pub struct BufferBuilder {
+    #[nvn_proc(fn nvnBufferBuilderSetDevice(device: *const Device) -> *const BufferBuilder)]
+    pub set_device: (),
+    #[nvn_proc(fn nvnBufferBuilderSetDefaults() -> *const BufferBuilder)]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnBufferBuilderSetStorage(pool: *const MemoryPool, offset: isize, size: usize) -> *const BufferBuilder)]
+    pub set_storage: (),
+    #[nvn_proc(const fn nvnBufferBuilderGetMemoryPool() -> *const MemoryPool)]
+    pub get_memory_pool: (),
+    #[nvn_proc(const fn nvnBufferBuilderGetMemoryOffset() -> isize)]
+    pub get_memory_offset: (),
+    #[nvn_proc(const fn nvnBufferBuilderGetSize() -> usize)]
+    pub get_size: ()
+}
+
+#[nvn_struct(48, nvn_resolver)]
+pub struct Buffer {
+    #[nvn_proc(fn nvnBufferInitialize(builder: *const BufferBuilder) -> bool)]
+    pub initialize: (),
+    #[nvn_proc(fn nvnBufferFinalize())]
+    pub finalize: (),
+    #[nvn_proc(fn nvnBufferSetDebugLabel(label: *const c_char))]
+    pub set_name: (),
+    #[nvn_proc(const fn nvnBufferMap() -> *mut u8)]
+    pub map: (),
+    #[nvn_proc(const fn nvnBufferGetAddress() -> BufferAddress)]
+    pub get_address: (),
+    #[nvn_proc(const fn nvnBufferFlushMappedRange(offset: isize, size: usize))]
+    pub flush: (),
+    #[nvn_proc(const fn nvnBufferInvalidateMappedRange(offset: isize, size: usize))]
+    pub invalidate: (),
+    #[nvn_proc(const fn nvnBufferGetMemoryPool() -> *const MemoryPool)]
+    pub get_memory_pool: (),
+    #[nvn_proc(const fn nvnBufferGetMemoryOffset() -> isize)]
+    pub get_memory_offset: (),
+    #[nvn_proc(const fn nvnBufferGetSize() -> usize)]
+    pub get_size: ()
 }
 
 #[nvn_struct(160, nvn_resolver)]
@@ -186,8 +503,340 @@ pub struct CommandBuffer {
     pub set_viewport: (),
     #[nvn_proc(fn nvnCommandBufferClearColor(index: i32, color: *const f32, mask: ClearColorMask))]
     pub clear_color: (),
-    #[nvn_proc(fn nvnCommandBufferClearTexture(texture: *const Texture, texture_view: *const u8, copy_region: *const u8, color: *const f32, mask: ClearColorMask))]
+    #[nvn_proc(fn nvnCommandBufferClearTexture(texture: *const Texture, texture_view: *const TextureView, copy_region: *const CopyRegion, color: *const f32, mask: ClearColorMask))]
     pub clear_texture: (),
+    #[nvn_proc(fn nvnCommandBufferBindProgram(program: *const Program, stages: ShaderStageBits))]
+    pub bind_program: (),
+    #[nvn_proc(fn nvnCommandBufferBindBlendState(state: *const BlendState))]
+    pub bind_blend_state: (),
+    #[nvn_proc(fn nvnCommandBufferBindChannelMaskState(state: *const ChannelMaskState))]
+    pub bind_channel_mask_state: (),
+    #[nvn_proc(fn nvnCommandBufferBindColorState(state: *const ColorState))]
+    pub bind_color_state: (),
+    #[nvn_proc(fn nvnCommandBufferBindDepthStencilState(state: *const DepthStencilState))]
+    pub bind_depth_stencil_state: (),
+    #[nvn_proc(fn nvnCommandBufferBindMultisampleState(state: *const MultisampleState))]
+    pub bind_multisample_state: (),
+    #[nvn_proc(fn nvnCommandBufferBindPolygonState(state: *const PolygonState))]
+    pub bind_polygon_state: (),
+    #[nvn_proc(fn nvnCommandBufferSetBlendColor(color: *const f32))]
+    pub set_blend_color: (),
+    #[nvn_proc(fn nvnCommandBufferSetStencilValueMask(faces: Face, mask: i32))]
+    pub set_stencil_value_mask: (),
+    #[nvn_proc(fn nvnCommandBufferSetStencilMask(faces: Face, mask: i32))]
+    pub set_stencil_mask: (),
+    #[nvn_proc(fn nvnCommandBufferSetStencilRef(faces: Face, reference: i32))]
+    pub set_stencil_ref: (),
+    #[nvn_proc(fn nvnCommandBufferBindVertexAttribState(count: i32, attribs: *const VertexAttribState))]
+    pub bind_vertex_attrib_state: (),
+    #[nvn_proc(fn nvnCommandBufferBindVertexStreamState(count: i32, streams: *const VertexStreamState))]
+    pub bind_vertex_stream_state: (),
+    #[nvn_proc(fn nvnCommandBufferBindVertexBuffer(index: i32, address: BufferAddress, size: usize))]
+    pub bind_vertex_buffer: (),
+    #[nvn_proc(fn nvnCommandBufferBindVertexBuffers(first: i32, count: i32, buffers: *const BufferRange))]
+    pub bind_vertex_buffers: (),
+    #[nvn_proc(fn nvnCommandBufferDrawArrays(mode: DrawPrimitive, first: i32, count: i32))]
+    pub draw_arrays: (),
+    #[nvn_proc(fn nvnCommandBufferDrawElements(mode: DrawPrimitive, index_type: IndexType, count: i32, index_buffer: BufferAddress))]
+    pub draw_elements: (),
+    #[nvn_proc(fn nvnCommandBufferDrawElementsBaseVertex(mode: DrawPrimitive, index_type: IndexType, count: i32, index_buffer: BufferAddress, base_vertex: i32))]
+    pub draw_elements_base_vertex: (),
+    #[nvn_proc(fn nvnCommandBufferDrawArraysInstanced(mode: DrawPrimitive, first: i32, count: i32, base_instance: i32, instance_count: i32))]
+    pub draw_arrays_instanced: (),
+    #[nvn_proc(fn nvnCommandBufferDrawElementsInstanced(mode: DrawPrimitive, index_type: IndexType, count: i32, index_buffer: BufferAddress, base_vertex: i32, base_instance: i32, instance_count: i32))]
+    pub draw_elements_instanced: (),
+    #[nvn_proc(fn nvnCommandBufferDrawArraysIndirect(mode: DrawPrimitive, indirect_buffer: BufferAddress))]
+    pub draw_arrays_indirect: (),
+    #[nvn_proc(fn nvnCommandBufferDrawElementsIndirect(mode: DrawPrimitive, index_type: IndexType, index_buffer: BufferAddress, indirect_buffer: BufferAddress))]
+    pub draw_elements_indirect: (),
+    #[nvn_proc(fn nvnCommandBufferMultiDrawArraysIndirectCount(mode: DrawPrimitive, indirect_buffer: BufferAddress, parameter_buffer: BufferAddress, max_draw_count: i32, stride: isize))]
+    pub multi_draw_arrays_indirect_count: (),
+    #[nvn_proc(fn nvnCommandBufferMultiDrawElementsIndirectCount(mode: DrawPrimitive, index_type: IndexType, index_buffer: BufferAddress, indirect_buffer: BufferAddress, parameter_buffer: BufferAddress, max_draw_count: i32, stride: isize))]
+    pub multi_draw_elements_indirect_count: (),
+    #[nvn_proc(fn nvnCommandBufferDispatchCompute(groups_x: i32, groups_y: i32, groups_z: i32))]
+    pub dispatch_compute: (),
+    #[nvn_proc(fn nvnCommandBufferDispatchComputeIndirect(indirect_buffer: BufferAddress))]
+    pub dispatch_compute_indirect: (),
+    #[nvn_proc(fn nvnCommandBufferSetTexturePool(pool: *const TexturePool))]
+    pub set_texture_pool: (),
+    #[nvn_proc(fn nvnCommandBufferBindStorageBuffer(stage: ShaderStage, index: i32, address: BufferAddress, size: usize))]
+    pub bind_storage_buffer: (),
+    #[nvn_proc(fn nvnCommandBufferBindStorageBuffers(stage: ShaderStage, first: i32, count: i32, buffers: *const BufferRange))]
+    pub bind_storage_buffers: (),
+    #[nvn_proc(fn nvnCommandBufferBindImage(stage: ShaderStage, index: i32, image: ImageHandle))]
+    pub bind_image: (),
+    #[nvn_proc(fn nvnCommandBufferBindImages(stage: ShaderStage, first: i32, count: i32, images: *const ImageHandle))]
+    pub bind_images: (),
+    #[nvn_proc(fn nvnCommandBufferBindUniformBuffer(stage: ShaderStage, index: i32, address: BufferAddress, size: usize))]
+    pub bind_uniform_buffer: (),
+    #[nvn_proc(fn nvnCommandBufferBindUniformBuffers(stage: ShaderStage, first: i32, count: i32, buffers: *const BufferRange))]
+    pub bind_uniform_buffers: (),
+    #[nvn_proc(fn nvnCommandBufferUpdateUniformBuffer(address: BufferAddress, buffer_size: usize, update_offset: isize, update_size: usize, data: *const u8))]
+    pub update_uniform_buffer: (),
+    #[nvn_proc(fn nvnCommandBufferCopyBufferToBuffer(src: BufferAddress, dst: BufferAddress, size: usize, flags: CopyFlags))]
+    pub copy_buffer_to_buffer: (),
+    #[nvn_proc(fn nvnCommandBufferCopyBufferToTexture(src: BufferAddress, dst: *const Texture, dst_view: *const TextureView, dst_region: *const CopyRegion, flags: CopyFlags))]
+    pub copy_buffer_to_texture: (),
+    #[nvn_proc(fn nvnCommandBufferCopyTextureToBuffer(src: *const Texture, src_view: *const TextureView, src_region: *const CopyRegion, dst: BufferAddress, flags: CopyFlags))]
+    pub copy_texture_to_buffer: (),
+    #[nvn_proc(fn nvnCommandBufferCopyTextureToTexture(src: *const Texture, src_view: *const TextureView, src_region: *const CopyRegion, dst: *const Texture, dst_view: *const TextureView, dst_region: *const CopyRegion, flags: CopyFlags))]
+    pub copy_texture_to_texture: (),
+    #[nvn_proc(fn nvnCommandBufferFenceSync(sync: *mut SyncObject, condition: SyncCondition, flags: SyncFlags))]
+    pub fence_sync: (),
+    #[nvn_proc(fn nvnCommandBufferWaitSync(sync: *const SyncObject))]
+    pub wait_sync: (),
+    #[nvn_proc(fn nvnCommandBufferReportCounter(counter: CounterType, buffer: BufferAddress))]
+    pub report_counter: (),
+    #[nvn_proc(fn nvnCommandBufferResetCounter(counter: CounterType))]
+    pub reset_counter: (),
+    #[nvn_proc(fn nvnCommandBufferBarrier(barriers: BarrierBits))]
+    pub barrier: (),
+    #[nvn_proc(fn nvnCommandBufferSetRenderEnable(enable: bool))]
+    pub set_render_enable: (),
+    #[nvn_proc(fn nvnCommandBufferSetRenderEnableConditional(mode: ConditionalRenderMode, counters: BufferAddress))]
+    pub set_render_enable_conditional: (),
+    #[nvn_proc(fn nvnCommandBufferSignalEvent(event: *const Event, mode: EventSignalMode, location: EventSignalLocation, flags: EventSignalFlags, value: u32))]
+    pub signal_event: (),
+    #[nvn_proc(fn nvnCommandBufferWaitEvent(event: *const Event, mode: EventWaitMode, value: u32))]
+    pub wait_event: (),
+    #[nvn_proc(fn nvnCommandBufferSetMemoryCallback(callback: CommandBufferMemoryCallback))]
+    pub set_memory_callback: (),
+    #[nvn_proc(fn nvnCommandBufferSetMemoryCallbackData(data: *mut c_void))]
+    pub set_memory_callback_data: (),
+    #[nvn_proc(const fn nvnCommandBufferGetCommandMemorySize() -> usize)]
+    pub get_command_memory_size: (),
+    #[nvn_proc(const fn nvnCommandBufferGetCommandMemoryUsed() -> usize)]
+    pub get_command_memory_used: (),
+    #[nvn_proc(const fn nvnCommandBufferGetCommandMemoryFree() -> usize)]
+    pub get_command_memory_free: (),
+    #[nvn_proc(const fn nvnCommandBufferGetControlMemorySize() -> usize)]
+    pub get_control_memory_size: (),
+    #[nvn_proc(const fn nvnCommandBufferGetControlMemoryUsed() -> usize)]
+    pub get_control_memory_used: (),
+    #[nvn_proc(const fn nvnCommandBufferGetControlMemoryFree() -> usize)]
+    pub get_control_memory_free: (),
+}
+
+/// Called by the driver when a command buffer runs out of command or control memory. The callback
+/// has to add at least `min_size` bytes of the requested kind before returning.
+pub type CommandBufferMemoryCallback = Option<extern "C" fn(buffer: *mut CommandBuffer, event: CommandBufferMemoryEvent, min_size: usize, data: *mut c_void)>;
+
+#[nvn_struct(32, nvn_resolver)]
+pub struct EventBuilder {
+    #[nvn_proc(fn nvnEventBuilderSetDefaults() -> *const EventBuilder)]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnEventBuilderSetStorage(pool: *const MemoryPool, offset: i64) -> *const EventBuilder)]
+    pub set_storage: (),
+    #[nvn_proc(const fn nvnEventBuilderGetStorage(offset: *mut i64) -> *const MemoryPool)]
+    pub get_storage: ()
+}
+
+#[nvn_struct(64, nvn_resolver)]
+pub struct Event {
+    #[nvn_proc(fn nvnEventInitialize(builder: *const EventBuilder) -> bool)]
+    pub initialize: (),
+    #[nvn_proc(fn nvnEventFinalize())]
+    pub finalize: (),
+    #[nvn_proc(const fn nvnEventGetValue() -> u32)]
+    pub get_value: (),
+    #[nvn_proc(fn nvnEventSignal(mode: EventSignalMode, value: u32))]
+    pub signal: (),
+    #[nvn_proc(const fn nvnEventGetMemoryPool() -> *const MemoryPool)]
+    pub get_memory_pool: (),
+    #[nvn_proc(const fn nvnEventGetMemoryOffset() -> i64)]
+    pub get_memory_offset: ()
+}
+
+#[nvn_struct(32, nvn_resolver)]
+pub struct TexturePool {
+    #[nvn_proc(fn nvnTexturePoolInitialize(pool: *const MemoryPool, offset: isize, descriptor_count: i32) -> bool)]
+    pub initialize: (),
+    #[nvn_proc(fn nvnTexturePoolFinalize())]
+    pub finalize: (),
+    #[nvn_proc(fn nvnTexturePoolSetDebugLabel(label: *const c_char))]
+    pub set_name: (),
+    #[nvn_proc(const fn nvnTexturePoolRegisterTexture(id: i32, texture: *const Texture, texture_view: *const TextureView))]
+    pub register_texture: (),
+    #[nvn_proc(const fn nvnTexturePoolRegisterImage(id: i32, texture: *const Texture, texture_view: *const TextureView))]
+    pub register_image: (),
+    #[nvn_proc(const fn nvnTexturePoolGetMemoryPool() -> *const MemoryPool)]
+    pub get_memory_pool: (),
+    #[nvn_proc(const fn nvnTexturePoolGetMemoryOffset() -> isize)]
+    pub get_memory_offset: (),
+    #[nvn_proc(const fn nvnTexturePoolGetSize() -> i32)]
+    pub get_size: ()
+}
+
+#[nvn_struct(192, nvn_resolver)]
+pub struct Program {
+    #[nvn_proc(fn nvnProgramInitialize(device: *const Device) -> bool)]
+    pub initialize: (),
+    #[nvn_proc(fn nvnProgramFinalize())]
+    pub finalize: (),
+    #[nvn_proc(fn nvnProgramSetDebugLabel(label: *const c_char))]
+    pub set_name: (),
+    #[nvn_proc(fn nvnProgramSetShaders(count: i32, stage_data: *const ShaderData) -> bool)]
+    pub set_shaders: ()
+}
+
+#[nvn_struct(8, nvn_resolver)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BlendState {
+    #[nvn_proc(fn nvnBlendStateSetDefaults())]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnBlendStateSetBlendTarget(index: i32))]
+    pub set_blend_target: (),
+    #[nvn_proc(fn nvnBlendStateSetBlendFunc(src_rgb: BlendFunc, dst_rgb: BlendFunc, src_alpha: BlendFunc, dst_alpha: BlendFunc))]
+    pub set_blend_func: (),
+    #[nvn_proc(fn nvnBlendStateSetBlendEquation(mode_rgb: BlendEquation, mode_alpha: BlendEquation))]
+    pub set_blend_equation: (),
+    #[nvn_proc(const fn nvnBlendStateGetBlendTarget() -> i32)]
+    pub get_blend_target: (),
+    #[nvn_proc(const fn nvnBlendStateGetBlendFunc(src_rgb: *mut BlendFunc, dst_rgb: *mut BlendFunc, src_alpha: *mut BlendFunc, dst_alpha: *mut BlendFunc))]
+    pub get_blend_func: (),
+    #[nvn_proc(const fn nvnBlendStateGetBlendEquation(mode_rgb: *mut BlendEquation, mode_alpha: *mut BlendEquation))]
+    pub get_blend_equation: ()
+}
+
+#[nvn_struct(4, nvn_resolver)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ColorState {
+    #[nvn_proc(fn nvnColorStateSetDefaults())]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnColorStateSetBlendEnable(index: i32, enable: bool))]
+    pub set_blend_enable: (),
+    #[nvn_proc(fn nvnColorStateSetLogicOp(op: LogicOp))]
+    pub set_logic_op: (),
+    #[nvn_proc(fn nvnColorStateSetAlphaTest(func: AlphaFunc))]
+    pub set_alpha_test: (),
+    #[nvn_proc(const fn nvnColorStateGetBlendEnable(index: i32) -> bool)]
+    pub get_blend_enable: (),
+    #[nvn_proc(const fn nvnColorStateGetLogicOp() -> LogicOp)]
+    pub get_logic_op: (),
+    #[nvn_proc(const fn nvnColorStateGetAlphaTest() -> AlphaFunc)]
+    pub get_alpha_test: ()
+}
+
+#[nvn_struct(4, nvn_resolver)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ChannelMaskState {
+    #[nvn_proc(fn nvnChannelMaskStateSetDefaults())]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnChannelMaskStateSetChannelMask(index: i32, r: bool, g: bool, b: bool, a: bool))]
+    pub set_channel_mask: (),
+    #[nvn_proc(const fn nvnChannelMaskStateGetChannelMask(index: i32, r: *mut bool, g: *mut bool, b: *mut bool, a: *mut bool))]
+    pub get_channel_mask: ()
+}
+
+#[nvn_struct(8, nvn_resolver)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DepthStencilState {
+    #[nvn_proc(fn nvnDepthStencilStateSetDefaults())]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnDepthStencilStateSetDepthTestEnable(enable: bool))]
+    pub set_depth_test_enable: (),
+    #[nvn_proc(fn nvnDepthStencilStateSetDepthWriteEnable(enable: bool))]
+    pub set_depth_write_enable: (),
+    #[nvn_proc(fn nvnDepthStencilStateSetDepthFunc(func: DepthFunc))]
+    pub set_depth_func: (),
+    #[nvn_proc(fn nvnDepthStencilStateSetStencilTestEnable(enable: bool))]
+    pub set_stencil_test_enable: (),
+    #[nvn_proc(fn nvnDepthStencilStateSetStencilFunc(faces: Face, func: StencilFunc))]
+    pub set_stencil_func: (),
+    #[nvn_proc(fn nvnDepthStencilStateSetStencilOp(faces: Face, fail: StencilOp, depth_fail: StencilOp, depth_pass: StencilOp))]
+    pub set_stencil_op: (),
+    #[nvn_proc(const fn nvnDepthStencilStateGetDepthTestEnable() -> bool)]
+    pub get_depth_test_enable: (),
+    #[nvn_proc(const fn nvnDepthStencilStateGetDepthWriteEnable() -> bool)]
+    pub get_depth_write_enable: (),
+    #[nvn_proc(const fn nvnDepthStencilStateGetDepthFunc() -> DepthFunc)]
+    pub get_depth_func: (),
+    #[nvn_proc(const fn nvnDepthStencilStateGetStencilTestEnable() -> bool)]
+    pub get_stencil_test_enable: (),
+    #[nvn_proc(const fn nvnDepthStencilStateGetStencilFunc(face: Face) -> StencilFunc)]
+    pub get_stencil_func: ()
+}
+
+#[nvn_struct(24, nvn_resolver)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MultisampleState {
+    #[nvn_proc(fn nvnMultisampleStateSetDefaults())]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnMultisampleStateSetMultisampleEnable(enable: bool))]
+    pub set_multisample_enable: (),
+    #[nvn_proc(fn nvnMultisampleStateSetSamples(samples: i32))]
+    pub set_samples: (),
+    #[nvn_proc(fn nvnMultisampleStateSetAlphaToCoverageEnable(enable: bool))]
+    pub set_alpha_to_coverage_enable: (),
+    #[nvn_proc(fn nvnMultisampleStateSetAlphaToCoverageDither(enable: bool))]
+    pub set_alpha_to_coverage_dither: (),
+    #[nvn_proc(fn nvnMultisampleStateSetRasterSamples(samples: i32))]
+    pub set_raster_samples: (),
+    #[nvn_proc(fn nvnMultisampleStateSetCoverageModulationMode(mode: CoverageModulationMode))]
+    pub set_coverage_modulation_mode: (),
+    #[nvn_proc(const fn nvnMultisampleStateGetMultisampleEnable() -> bool)]
+    pub get_multisample_enable: (),
+    #[nvn_proc(const fn nvnMultisampleStateGetSamples() -> i32)]
+    pub get_samples: (),
+    #[nvn_proc(const fn nvnMultisampleStateGetAlphaToCoverageEnable() -> bool)]
+    pub get_alpha_to_coverage_enable: (),
+    #[nvn_proc(const fn nvnMultisampleStateGetRasterSamples() -> i32)]
+    pub get_raster_samples: (),
+    #[nvn_proc(const fn nvnMultisampleStateGetCoverageModulationMode() -> CoverageModulationMode)]
+    pub get_coverage_modulation_mode: ()
+}
+
+#[nvn_struct(4, nvn_resolver)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PolygonState {
+    #[nvn_proc(fn nvnPolygonStateSetDefaults())]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnPolygonStateSetCullFace(face: Face))]
+    pub set_cull_face: (),
+    #[nvn_proc(fn nvnPolygonStateSetFrontFace(face: FrontFace))]
+    pub set_front_face: (),
+    #[nvn_proc(fn nvnPolygonStateSetPolygonMode(mode: PolygonMode))]
+    pub set_polygon_mode: (),
+    #[nvn_proc(fn nvnPolygonStateSetPolygonOffsetEnables(enables: PolygonOffsetEnable))]
+    pub set_polygon_offset_enables: (),
+    #[nvn_proc(const fn nvnPolygonStateGetCullFace() -> Face)]
+    pub get_cull_face: (),
+    #[nvn_proc(const fn nvnPolygonStateGetFrontFace() -> FrontFace)]
+    pub get_front_face: (),
+    #[nvn_proc(const fn nvnPolygonStateGetPolygonMode() -> PolygonMode)]
+    pub get_polygon_mode: (),
+    #[nvn_proc(const fn nvnPolygonStateGetPolygonOffsetEnables() -> PolygonOffsetEnable)]
+    pub get_polygon_offset_enables: ()
+}
+
+#[nvn_struct(4, nvn_resolver)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VertexAttribState {
+    #[nvn_proc(fn nvnVertexAttribStateSetDefaults())]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnVertexAttribStateSetFormat(format: Format, relative_offset: isize))]
+    pub set_format: (),
+    #[nvn_proc(fn nvnVertexAttribStateSetStreamIndex(index: i32))]
+    pub set_stream_index: (),
+    #[nvn_proc(const fn nvnVertexAttribStateGetFormat(format: *mut Format, relative_offset: *mut isize))]
+    pub get_format: (),
+    #[nvn_proc(const fn nvnVertexAttribStateGetStreamIndex() -> i32)]
+    pub get_stream_index: ()
+}
+
+#[nvn_struct(8, nvn_resolver)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VertexStreamState {
+    #[nvn_proc(fn nvnVertexStreamStateSetDefaults())]
+    pub set_defaults: (),
+    #[nvn_proc(fn nvnVertexStreamStateSetStride(stride: isize))]
+    pub set_stride: (),
+    #[nvn_proc(fn nvnVertexStreamStateSetDivisor(divisor: i32))]
+    pub set_divisor: (),
+    #[nvn_proc(const fn nvnVertexStreamStateGetStride() -> isize)]
+    pub get_stride: (),
+    #[nvn_proc(const fn nvnVertexStreamStateGetDivisor() -> i32)]
+    pub get_divisor: ()
 }
 
 #[nvn_struct(64, nvn_resolver)]
@@ -200,8 +849,14 @@ pub struct MemoryPoolBuilder {
     pub set_flags: (),
     #[nvn_proc(fn nvnMemoryPoolBuilderSetStorage(memory: *const u8, size: usize) -> *const MemoryPoolBuilder)]
     pub set_storage: (),
+    #[nvn_proc(const fn nvnMemoryPoolBuilderGetDevice() -> *const Device)]
+    pub get_device: (),
     #[nvn_proc(const fn nvnMemoryPoolBuilderGetMemory() -> *mut u8)]
-    pub get_memory: ()
+    pub get_memory: (),
+    #[nvn_proc(const fn nvnMemoryPoolBuilderGetSize() -> usize)]
+    pub get_size: (),
+    #[nvn_proc(const fn nvnMemoryPoolBuilderGetFlags() -> MemoryPoolFlags)]
+    pub get_flags: ()
 }
 
 #[nvn_struct(256, nvn_resolver)]
@@ -218,7 +873,30 @@ pub struct MemoryPool {
     #[nvn_proc(const fn nvnMemoryPoolMap() -> *mut u8)]
     pub map: (),
     #[nvn_proc(const fn nvnMemoryPoolFlushMappedRange(offset: usize, size: usize))]
-    pub flush: ()
+    pub flush: (),
+    #[nvn_proc(const fn nvnMemoryPoolInvalidateMappedRange(offset: usize, size: usize))]
+    pub invalidate: (),
+    #[nvn_proc(const fn nvnMemoryPoolGetBufferAddress() -> BufferAddress)]
+    pub get_buffer_address: ()
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceFlags {
+    pub debug_enable: bool,
+    pub debug_skip_calls_on_error: bool,
+    pub debug_draw_validation: bool,
+    pub deferred_finalize: bool,
+    pub debug_draw_validation_heavy: bool,
+    pub debug_enable_level_0: bool,
+    pub debug_enable_level_1: bool,
+    pub debug_enable_level_2: bool,
+    pub debug_enable_level_3: bool,
+    pub debug_enable_level_4: bool,
+    pub deferred_firmware_memory_reclaim: bool,
+    pub enable_separate_sampler_texture_support: bool,
+    unused: B20,
 }
 
 #[bitfield]
@@ -247,4 +925,818 @@ pub struct ClearColorMask {
     pub b: bool,
     pub rgba: bool,
     unused: B28,
-}
\ No newline at end of file
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy)]
+pub struct CopyFlags {
+    pub linear_filter: bool,
+    pub engine_2d: bool,
+    pub mirror_x: bool,
+    pub mirror_y: bool,
+    pub mirror_z: bool,
+    unused: B27,
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy)]
+pub struct BarrierBits {
+    pub order_primitives: bool,
+    pub order_fragments: bool,
+    pub order_fragments_tiled: bool,
+    pub order_indirect_data: bool,
+    pub invalidate_texture: bool,
+    pub invalidate_shader: bool,
+    pub invalidate_texture_descriptor: bool,
+    pub invalidate_zcull: bool,
+    pub invalidate_l2_cache: bool,
+    unused: B23,
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy)]
+pub struct EventSignalFlags {
+    pub no_wait_memory: bool,
+    unused: B31,
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy)]
+pub struct SyncFlags {
+    pub flush_for_cpu: bool,
+    unused: B31,
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderStageBits {
+    pub vertex: bool,
+    pub fragment: bool,
+    pub geometry: bool,
+    pub tess_control: bool,
+    pub tess_evaluation: bool,
+    pub compute: bool,
+    unused: B26,
+}
+
+impl ShaderStageBits {
+    pub fn all_graphics() -> Self {
+        Self::new()
+            .with_vertex(true)
+            .with_fragment(true)
+            .with_geometry(true)
+            .with_tess_control(true)
+            .with_tess_evaluation(true)
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ShaderStage {
+    Vertex = 0,
+    Fragment = 1,
+    Geometry = 2,
+    TessControl = 3,
+    TessEvaluation = 4,
+    Compute = 5,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DeviceInfo {
+    ApiMajorVersion,
+    ApiMinorVersion,
+    UniformBufferBindingsPerStage,
+    MaxUniformBufferSize,
+    UniformBufferAlignment,
+    ColorBufferBindings,
+    VertexBufferBindings,
+    TransformFeedbackBufferBindings,
+    ShaderStorageBufferBindingsPerStage,
+    TextureBindingsPerStage,
+    CounterAlignment,
+    TransformFeedbackBufferAlignment,
+    TransformFeedbackControlAlignment,
+    IndirectDrawAlignment,
+    VertexAttributes,
+    TextureDescriptorSize,
+    SamplerDescriptorSize,
+    ReservedTextureDescriptors,
+    ReservedSamplerDescriptors,
+    CommandBufferCommandAlignment,
+    CommandBufferControlAlignment,
+    CommandBufferMinCommandSize,
+    CommandBufferMinControlSize,
+    ShaderScratchMemoryScaleFactorMinimum,
+    ShaderScratchMemoryScaleFactorRecommended,
+    ShaderScratchMemoryAlignment,
+    ShaderScratchMemoryGranularity,
+    MaxTextureAnisotropy,
+    MaxComputeWorkGroupSizeX,
+    MaxComputeWorkGroupSizeY,
+    MaxComputeWorkGroupSizeZ,
+    MaxComputeWorkGroupSizeThreads,
+    MaxComputeDispatchWorkGroupsX,
+    MaxComputeDispatchWorkGroupsY,
+    MaxComputeDispatchWorkGroupsZ,
+    ImageBindingsPerStage,
+    MaxTexturePoolSize,
+    MaxSamplerPoolSize,
+    MaxViewports,
+    MempoolTextureObjectPageAlignment,
+    SupportsMinMaxFiltering,
+    SupportsStencil8Format,
+    SupportsAstcFormats,
+    L2Size,
+    MaxTextureLevels,
+    MaxTextureLayers,
+    GlslcMinSupportedGpuCodeMajorVersion,
+    GlslcMaxSupportedGpuCodeMajorVersion,
+    GlslcMinSupportedGpuCodeMinorVersion,
+    GlslcMaxSupportedGpuCodeMinorVersion,
+    SupportsConservativeRaster,
+    SubpixelBits,
+    MaxSubpixelBiasBits,
+    IndirectDispatchAlignment,
+    ZcullSaveRestoreAlignment,
+    ShaderScratchMemoryComputeScaleFactorMinimum,
+    LinearTextureStrideAlignment,
+    LinearRenderTargetStrideAlignment,
+    MemoryPoolPageSize,
+    SupportsZeroFromUnmappedVirtualPoolPages,
+    UniformBufferUpdateAlignment,
+    MaxTextureSize,
+    MaxBufferTextureSize,
+    Max3DTextureSize,
+    MaxCubeMapTextureSize,
+    MaxRectangleTextureSize,
+    SupportsPassthroughGeometryShaders,
+    SupportsViewportSwizzle,
+    SupportsSparseTiledPackagedTextures,
+    SupportsAdvancedBlendModes,
+    MaxPresentInterval,
+    SupportsDrawTexture,
+    SupportsTargetIndependentRasterization,
+    SupportsFragmentCoverageToColor,
+    SupportsPostDepthCoverage,
+    SupportsImagesUsingTextureHandles,
+    SupportsSampleLocations,
+    MaxSampleLocationTableEntries,
+    ShaderCodeAlignment,
+    ShaderCodeMemoryPoolPaddingSize,
+    MaxPatchSize,
+    QueueCommandMemoryGranularity,
+    QueueCommandMemoryMinSize,
+    QueueCommandMemoryDefaultSize,
+    QueueComputeMemoryGranularity,
+    QueueComputeMemoryMinSize,
+    QueueComputeMemoryDefaultSize,
+    QueueCommandMemoryMinFlushThreshold,
+    SupportsFragmentShaderInterlock,
+    MaxTexturesPerWindow,
+    MinTexturesPerWindow,
+    SupportsDebugLayer,
+    QueueControlMemoryMinSize,
+    QueueControlMemoryDefaultSize,
+    QueueControlMemoryGranularity,
+    SeparateTextureBindingsPerStage,
+    SeparateSamplerBindingsPerStage,
+    DebugGroupsMaxDomainId,
+    EventsSupportReductionOperations,
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Debug, Clone, Copy)]
+pub struct PolygonOffsetEnable {
+    pub point: bool,
+    pub line: bool,
+    pub fill: bool,
+    unused: B29,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BlendFunc {
+    Zero = 1,
+    One = 2,
+    SrcColor = 3,
+    OneMinusSrcColor = 4,
+    SrcAlpha = 5,
+    OneMinusSrcAlpha = 6,
+    DstAlpha = 7,
+    OneMinusDstAlpha = 8,
+    DstColor = 9,
+    OneMinusDstColor = 10,
+    SrcAlphaSaturate = 11,
+    Src1Color = 16,
+    OneMinusSrc1Color = 17,
+    Src1Alpha = 18,
+    OneMinusSrc1Alpha = 19,
+    ConstantColor = 97,
+    OneMinusConstantColor = 98,
+    ConstantAlpha = 99,
+    OneMinusConstantAlpha = 100,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BlendEquation {
+    Add = 1,
+    Sub = 2,
+    ReverseSub = 3,
+    Min = 4,
+    Max = 5,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LogicOp {
+    Clear = 0,
+    And = 1,
+    AndReverse = 2,
+    Copy = 3,
+    AndInverted = 4,
+    Noop = 5,
+    Xor = 6,
+    Or = 7,
+    Nor = 8,
+    Equiv = 9,
+    Invert = 10,
+    OrReverse = 11,
+    CopyInverted = 12,
+    OrInverted = 13,
+    Nand = 14,
+    Set = 15,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AlphaFunc {
+    Never = 1,
+    Less = 2,
+    Equal = 3,
+    Lequal = 4,
+    Greater = 5,
+    Notequal = 6,
+    Gequal = 7,
+    Always = 8,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DepthFunc {
+    Never = 1,
+    Less = 2,
+    Equal = 3,
+    Lequal = 4,
+    Greater = 5,
+    Notequal = 6,
+    Gequal = 7,
+    Always = 8,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StencilFunc {
+    Never = 1,
+    Less = 2,
+    Equal = 3,
+    Lequal = 4,
+    Greater = 5,
+    Notequal = 6,
+    Gequal = 7,
+    Always = 8,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StencilOp {
+    Keep = 1,
+    Zero = 2,
+    Replace = 3,
+    Incr = 4,
+    Decr = 5,
+    Invert = 6,
+    IncrWrap = 7,
+    DecrWrap = 8,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Face {
+    None = 0,
+    Front = 1,
+    Back = 2,
+    FrontAndBack = 3,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FrontFace {
+    Cw = 0,
+    Ccw = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PolygonMode {
+    Point = 0,
+    Line = 1,
+    Fill = 2,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CoverageModulationMode {
+    None = 0,
+    Rgb = 1,
+    Alpha = 2,
+    Rgba = 3,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TextureTarget {
+    Target1D = 0,
+    Target2D = 1,
+    Target3D = 2,
+    Target1DArray = 3,
+    Target2DArray = 4,
+    Target2DMultisample = 5,
+    Target2DMultisampleArray = 6,
+    Rectangle = 7,
+    CubeMap = 8,
+    CubeMapArray = 9,
+    Buffer = 10,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    None = 0,
+    R8 = 1,
+    R8SN = 2,
+    R8UI = 3,
+    R8I = 4,
+    R16F = 5,
+    R16 = 6,
+    R16SN = 7,
+    R16UI = 8,
+    R16I = 9,
+    R32F = 10,
+    R32UI = 11,
+    R32I = 12,
+    RG8 = 13,
+    RG8SN = 14,
+    RG8UI = 15,
+    RG8I = 16,
+    RG16F = 17,
+    RG16 = 18,
+    RG16SN = 19,
+    RG16UI = 20,
+    RG16I = 21,
+    RG32F = 22,
+    RG32UI = 23,
+    RG32I = 24,
+    RGB8 = 25,
+    RGB8SN = 26,
+    RGB8UI = 27,
+    RGB8I = 28,
+    RGB16F = 29,
+    RGB16 = 30,
+    RGB16SN = 31,
+    RGB16UI = 32,
+    RGB16I = 33,
+    RGB32F = 34,
+    RGB32UI = 35,
+    RGB32I = 36,
+    RGBA8 = 37,
+    RGBA8SN = 38,
+    RGBA8UI = 39,
+    RGBA8I = 40,
+    RGBA16F = 41,
+    RGBA16 = 42,
+    RGBA16SN = 43,
+    RGBA16UI = 44,
+    RGBA16I = 45,
+    RGBA32F = 46,
+    RGBA32UI = 47,
+    RGBA32I = 48,
+    Stencil8 = 49,
+    Depth16 = 50,
+    Depth24 = 51,
+    Depth32F = 52,
+    Depth24Stencil8 = 53,
+    Depth32FStencil8 = 54,
+    RGBX8SRGB = 55,
+    RGBA8SRGB = 56,
+    RGBA4 = 57,
+    RGB5 = 58,
+    RGB5A1 = 59,
+    RGB565 = 60,
+    RGB10A2 = 61,
+    RGB10A2UI = 62,
+    R11G11B10F = 63,
+    RGB9E5F = 64,
+    RGBDXT1 = 65,
+    RGBADXT1 = 66,
+    RGBADXT3 = 67,
+    RGBADXT5 = 68,
+    RGBDXT1SRGB = 69,
+    RGBADXT1SRGB = 70,
+    RGBADXT3SRGB = 71,
+    RGBADXT5SRGB = 72,
+    RGTC1Unorm = 73,
+    RGTC1Snorm = 74,
+    RGTC2Unorm = 75,
+    RGTC2Snorm = 76,
+    BPTCUnorm = 77,
+    BPTCUnormSRGB = 78,
+    BPTCSfloat = 79,
+    BPTCUfloat = 80,
+    R8UI2F = 81,
+    R8I2F = 82,
+    R16UI2F = 83,
+    R16I2F = 84,
+    R32UI2F = 85,
+    R32I2F = 86,
+    RG8UI2F = 87,
+    RG8I2F = 88,
+    RG16UI2F = 89,
+    RG16I2F = 90,
+    RG32UI2F = 91,
+    RG32I2F = 92,
+    RGB8UI2F = 93,
+    RGB8I2F = 94,
+    RGB16UI2F = 95,
+    RGB16I2F = 96,
+    RGB32UI2F = 97,
+    RGB32I2F = 98,
+    RGBA8UI2F = 99,
+    RGBA8I2F = 100,
+    RGBA16UI2F = 101,
+    RGBA16I2F = 102,
+    RGBA32UI2F = 103,
+    RGBA32I2F = 104,
+    RGB10A2SN = 105,
+    RGB10A2I = 106,
+    RGB10A2UI2F = 107,
+    RGB10A2I2F = 108,
+    RGBX8 = 109,
+    RGBX8SN = 110,
+    RGBX8UI = 111,
+    RGBX8I = 112,
+    RGBX16F = 113,
+    RGBX16 = 114,
+    RGBX16SN = 115,
+    RGBX16UI = 116,
+    RGBX16I = 117,
+    RGBX32F = 118,
+    RGBX32UI = 119,
+    RGBX32I = 120,
+    RGBAASTC4x4 = 121,
+    RGBAASTC5x4 = 122,
+    RGBAASTC5x5 = 123,
+    RGBAASTC6x5 = 124,
+    RGBAASTC6x6 = 125,
+    RGBAASTC8x5 = 126,
+    RGBAASTC8x6 = 127,
+    RGBAASTC8x8 = 128,
+    RGBAASTC10x5 = 129,
+    RGBAASTC10x6 = 130,
+    RGBAASTC10x8 = 131,
+    RGBAASTC10x10 = 132,
+    RGBAASTC12x10 = 133,
+    RGBAASTC12x12 = 134,
+    RGBAASTC4x4SRGB = 135,
+    RGBAASTC5x4SRGB = 136,
+    RGBAASTC5x5SRGB = 137,
+    RGBAASTC6x5SRGB = 138,
+    RGBAASTC6x6SRGB = 139,
+    RGBAASTC8x5SRGB = 140,
+    RGBAASTC8x6SRGB = 141,
+    RGBAASTC8x8SRGB = 142,
+    RGBAASTC10x5SRGB = 143,
+    RGBAASTC10x6SRGB = 144,
+    RGBAASTC10x8SRGB = 145,
+    RGBAASTC10x10SRGB = 146,
+    RGBAASTC12x10SRGB = 147,
+    RGBAASTC12x12SRGB = 148,
+    BGR5 = 149,
+    BGR5A1 = 150,
+    BGR565 = 151,
+    A1BGR5 = 152,
+    BGRA8 = 153,
+    BGRX8 = 154,
+    BGRA8SRGB = 155,
+    BGRX8SRGB = 156,
+}
+
+impl Format {
+    /// Size of a single element of this format in bytes, or `None` for block-compressed formats.
+    pub fn size(self) -> Option<usize> {
+        match self {
+            Format::R8 | Format::R8SN | Format::R8UI | Format::R8I | Format::Stencil8 |
+            Format::R8UI2F | Format::R8I2F => Some(1),
+            Format::R16F | Format::R16 | Format::R16SN | Format::R16UI | Format::R16I |
+            Format::RG8 | Format::RG8SN | Format::RG8UI | Format::RG8I | Format::Depth16 |
+            Format::RGBA4 | Format::RGB5 | Format::RGB5A1 | Format::RGB565 | Format::R16UI2F |
+            Format::R16I2F | Format::RG8UI2F | Format::RG8I2F | Format::BGR5 | Format::BGR5A1 |
+            Format::BGR565 | Format::A1BGR5 => Some(2),
+            Format::RGB8 | Format::RGB8SN | Format::RGB8UI | Format::RGB8I | Format::RGB8UI2F |
+            Format::RGB8I2F => Some(3),
+            Format::R32F | Format::R32UI | Format::R32I | Format::RG16F | Format::RG16 |
+            Format::RG16SN | Format::RG16UI | Format::RG16I | Format::RGBA8 | Format::RGBA8SN |
+            Format::RGBA8UI | Format::RGBA8I | Format::Depth24 | Format::Depth32F |
+            Format::Depth24Stencil8 | Format::RGBX8SRGB | Format::RGBA8SRGB | Format::RGB10A2 |
+            Format::RGB10A2UI | Format::R11G11B10F | Format::RGB9E5F | Format::R32UI2F |
+            Format::R32I2F | Format::RG16UI2F | Format::RG16I2F | Format::RGBA8UI2F |
+            Format::RGBA8I2F | Format::RGB10A2SN | Format::RGB10A2I | Format::RGB10A2UI2F |
+            Format::RGB10A2I2F | Format::RGBX8 | Format::RGBX8SN | Format::RGBX8UI |
+            Format::RGBX8I | Format::BGRA8 | Format::BGRX8 | Format::BGRA8SRGB | Format::BGRX8SRGB => Some(4),
+            Format::RGB16F | Format::RGB16 | Format::RGB16SN | Format::RGB16UI | Format::RGB16I |
+            Format::RGB16UI2F | Format::RGB16I2F => Some(6),
+            Format::RG32F | Format::RG32UI | Format::RG32I | Format::RGBA16F | Format::RGBA16 |
+            Format::RGBA16SN | Format::RGBA16UI | Format::RGBA16I | Format::Depth32FStencil8 |
+            Format::RG32UI2F | Format::RG32I2F | Format::RGBA16UI2F | Format::RGBA16I2F |
+            Format::RGBX16F | Format::RGBX16 | Format::RGBX16SN | Format::RGBX16UI |
+            Format::RGBX16I => Some(8),
+            Format::RGB32F | Format::RGB32UI | Format::RGB32I | Format::RGB32UI2F |
+            Format::RGB32I2F => Some(12),
+            Format::RGBA32F | Format::RGBA32UI | Format::RGBA32I | Format::RGBA32UI2F |
+            Format::RGBA32I2F | Format::RGBX32F | Format::RGBX32UI | Format::RGBX32I => Some(16),
+            _ => None
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DrawPrimitive {
+    Points = 0,
+    Lines = 1,
+    LineLoop = 2,
+    LineStrip = 3,
+    Triangles = 4,
+    TriangleStrip = 5,
+    TriangleFan = 6,
+    Quads = 7,
+    QuadStrip = 8,
+    Polygon = 9,
+    LinesAdjacency = 10,
+    LineStripAdjacency = 11,
+    TrianglesAdjacency = 12,
+    TriangleStripAdjacency = 13,
+    Patches = 14,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum IndexType {
+    UnsignedByte = 0,
+    UnsignedShort = 1,
+    UnsignedInt = 2,
+}
+
+impl IndexType {
+    pub const fn size(self) -> usize {
+        match self {
+            IndexType::UnsignedByte => 1,
+            IndexType::UnsignedShort => 2,
+            IndexType::UnsignedInt => 4
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SyncCondition {
+    AllGpuCommandsComplete = 0,
+    GraphicsWorldSpaceComplete = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SyncWaitResult {
+    AlreadySignaled = 0,
+    ConditionSatisfied = 1,
+    TimeoutExpired = 2,
+    Failed = 3,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CounterType {
+    Timestamp = 0,
+    SamplesPassed = 1,
+    InputVertices = 2,
+    InputPrimitives = 3,
+    VertexShaderInvocations = 4,
+    TessControlShaderInvocations = 5,
+    TessEvaluationShaderInvocations = 6,
+    GeometryShaderInvocations = 7,
+    FragmentShaderInvocations = 8,
+    TessEvaluationShaderPrimitives = 9,
+    GeometryShaderPrimitives = 10,
+    ClipperInputPrimitives = 11,
+    ClipperOutputPrimitives = 12,
+    PrimitivesGenerated = 13,
+    TransformFeedbackPrimitivesWritten = 14,
+    ZcullStats = 15,
+    TimestampTop = 16,
+}
+
+/// Comparison applied by `set_render_enable_conditional` to the two 64-bit values at its address.
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ConditionalRenderMode {
+    RenderIfEqual = 0,
+    RenderIfNotEqual = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EventSignalMode {
+    Write = 0,
+    Add = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EventSignalLocation {
+    Top = 0,
+    VertexPipe = 1,
+    Bottom = 2,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EventWaitMode {
+    Equal = 0,
+    GequalWrap = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum QueueGetErrorResult {
+    GpuNoError = 0,
+    GpuErrorUnknown = 1,
+    GpuErrorMmuFault = 2,
+    GpuErrorPbdmaException = 3,
+    GpuErrorEngineException = 4,
+    GpuErrorTimeout = 5,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MmuFaultAccessType {
+    Read = 0,
+    Write = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WindowAcquireTextureResult {
+    Success = 0,
+    NativeError = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CommandBufferMemoryEvent {
+    OutOfCommandMemory = 0,
+    OutOfControlMemory = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WindowOriginMode {
+    LowerLeft = 0,
+    UpperLeft = 1,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DepthMode {
+    NearIsMinusW = 0,
+    NearIsZero = 1,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::{align_of, size_of};
+
+    macro_rules! assert_layout {
+        ($($ty:ty => ($size:expr, $align:expr)),* $(,)?) => {
+            $(
+                assert_eq!(size_of::<$ty>(), $size, "size of {}", stringify!($ty));
+                assert_eq!(align_of::<$ty>(), $align, "alignment of {}", stringify!($ty));
+            )*
+        }
+    }
+
+    #[test]
+    fn objects_match_driver_sizes() {
+        // objects are opaque byte arrays of the size the driver expects
+        assert_layout! {
+            DeviceBuilder => (0x40, 1),
+            QueueBuilder => (0x40, 1),
+            Device => (0x3000, 1),
+            Queue => (0x2000, 1),
+            WindowBuilder => (64, 1),
+            Window => (384, 1),
+            SyncObject => (64, 1),
+            Texture => (192, 1),
+            TextureView => (40, 1),
+            BufferBuilder => (64, 1),
+            Buffer => (48, 1),
+            CommandBuffer => (160, 1),
+            EventBuilder => (32, 1),
+            Event => (64, 1),
+            TexturePool => (32, 1),
+            Program => (192, 1),
+            MemoryPoolBuilder => (64, 1),
+            MemoryPool => (256, 1),
+        }
+    }
+
+    #[test]
+    fn states_match_driver_sizes() {
+        assert_layout! {
+            BlendState => (8, 1),
+            ColorState => (4, 1),
+            ChannelMaskState => (4, 1),
+            DepthStencilState => (8, 1),
+            MultisampleState => (24, 1),
+            PolygonState => (4, 1),
+            VertexAttribState => (4, 1),
+            VertexStreamState => (8, 1),
+        }
+    }
+
+    #[test]
+    fn gpu_data_layouts() {
+        assert_layout! {
+            BufferAddress => (8, 8),
+            BufferRange => (16, 8),
+            DrawArraysIndirectData => (16, 4),
+            DrawElementsIndirectData => (20, 4),
+            DispatchComputeIndirectData => (12, 4),
+            CopyRegion => (24, 4),
+            ReportValue => (16, 8),
+            CommandHandle => (8, 8),
+            TextureHandle => (8, 8),
+            ImageHandle => (8, 8),
+        }
+    }
+
+    #[test]
+    fn format_sizes() {
+        assert_eq!(Format::R8.size(), Some(1));
+        assert_eq!(Format::RGB565.size(), Some(2));
+        assert_eq!(Format::RGB8.size(), Some(3));
+        assert_eq!(Format::RGBA8.size(), Some(4));
+        assert_eq!(Format::Depth24Stencil8.size(), Some(4));
+        assert_eq!(Format::RGB16F.size(), Some(6));
+        assert_eq!(Format::Depth32FStencil8.size(), Some(8));
+        assert_eq!(Format::RGB32F.size(), Some(12));
+        assert_eq!(Format::RGBA32F.size(), Some(16));
+    }
+
+    #[test]
+    fn unsized_formats() {
+        assert_eq!(Format::None.size(), None);
+        assert_eq!(Format::RGBDXT1.size(), None);
+        assert_eq!(Format::RGBADXT5.size(), None);
+    }
+
+    #[test]
+    fn copy_region_fits() {
+        let region = CopyRegion::new(0, 0, 0, 64, 32, 1);
+        assert!(region.fits(64, 32, 1));
+        assert!(!region.fits(63, 32, 1));
+        assert!(!region.fits(64, 32, 0));
+
+        assert!(CopyRegion::new(16, 8, 0, 48, 24, 1).fits(64, 32, 1));
+        assert!(!CopyRegion::new(17, 8, 0, 48, 24, 1).fits(64, 32, 1));
+    }
+
+    #[test]
+    fn copy_region_rejects_empty_and_negative() {
+        assert!(!CopyRegion::new(0, 0, 0, 0, 32, 1).fits(64, 32, 1));
+        assert!(!CopyRegion::new(-1, 0, 0, 16, 16, 1).fits(64, 32, 1));
+        assert!(!CopyRegion::new(0, 0, 0, 16, -16, 1).fits(64, 32, 1));
+    }
+
+    #[test]
+    fn copy_region_end_overflow_does_not_fit() {
+        assert!(!CopyRegion::new(i32::MAX, 0, 0, 1, 1, 1).fits(i32::MAX, 1, 1));
+    }
+}
@@ -2,10 +2,41 @@ use super::*;
 
 use thiserror::Error;
 
+use std::ffi::CStr;
 use std::fmt;
+use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut, Range};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, Weak};
 use std::sync::atomic::{AtomicUsize, Ordering};
-pub struct InitializeError(&'static str);
+use std::time::{Duration, Instant};
+
+mod allocator;
+mod storage;
+
+pub use allocator::*;
+pub use storage::*;
+
+#[derive(Error)]
+#[error("Failed to initialize {0} object")]
+pub struct InitializeError(pub(crate) &'static str);
+
+impl InitializeError {
+    /// Type of the object that failed to initialize.
+    pub fn object(&self) -> &'static str {
+        self.0
+    }
+
+    /// Turns the `bool` returned by a raw `initialize` proc into a `Result`, so raw objects can be
+    /// set up with `?` as well.
+    pub fn check(initialized: bool, object: &'static str) -> Result<(), InitializeError> {
+        if initialized {
+            Ok(())
+        } else {
+            Err(InitializeError(object))
+        }
+    }
+}
 
 impl fmt::Debug for InitializeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -13,6 +44,50 @@ impl fmt::Debug for InitializeError {
     }
 }
 
+/// Invalid builder settings, caught before the driver is asked to initialize anything.
+#[derive(Error, Debug)]
+pub enum BuildError {
+    #[error("`device` was not set")]
+    MissingDevice,
+    #[error("`{0}` is null")]
+    NullMemory(&'static str),
+    #[error("`{param}` at {address:#x} is not aligned to {align:#x}")]
+    Misaligned {
+        param: &'static str,
+        address: usize,
+        align: usize
+    },
+    #[error("`{param}` size {size:#x} is not a non-zero multiple of {granularity:#x}")]
+    InvalidSize {
+        param: &'static str,
+        size: usize,
+        granularity: usize
+    },
+    #[error("`{param}` size {size:#x} is below the device minimum of {min:#x}")]
+    TooSmall {
+        param: &'static str,
+        size: usize,
+        min: usize
+    },
+    #[error("`flags` {0:?} sets both cpu_no_access and cpu_cached")]
+    ConflictingFlags(MemoryPoolFlags),
+    #[error("`{param}` index {index} is out of range, at most {max} are supported")]
+    IndexOutOfRange {
+        param: &'static str,
+        index: usize,
+        max: usize
+    },
+    #[error("`format` {0:?} has no element size")]
+    UnsizedFormat(Format),
+    #[error("Failed to allocate {size:#x} bytes of `{param}`")]
+    AllocationFailed {
+        param: &'static str,
+        size: usize
+    },
+    #[error(transparent)]
+    Initialize(#[from] InitializeError),
+}
+
 macro_rules! impl_init_err {
     ($ty:ty) => {
         impl $ty {
@@ -23,9 +98,156 @@ macro_rules! impl_init_err {
     }
 }
 
+const MEMORY_POOL_GRANULARITY: usize = 0x1000;
+
+const fn align_up(value: usize, align: usize) -> usize {
+    (value + align - 1) & !(align - 1)
+}
+
+pub struct DeviceBuilder {
+    builder: super::DeviceBuilder,
+    flags: DeviceFlags,
+    debug_level: Option<u8>,
+    window_origin_mode: Option<WindowOriginMode>,
+    depth_mode: Option<DepthMode>
+}
+
+impl DeviceBuilder {
+    pub fn new() -> Self {
+        Self {
+            builder: {
+                let mut ret = super::DeviceBuilder::new();
+                ret.set_defaults();
+                ret
+            },
+            flags: DeviceFlags::new(),
+            debug_level: None,
+            window_origin_mode: None,
+            depth_mode: None
+        }
+    }
+
+    pub fn with_flags(mut self, flags: DeviceFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Enables the debug layer at `level`, from 0 (lightest) to 4 (most thorough).
+    pub fn with_debug_layer(mut self, level: u8) -> Self {
+        self.debug_level = Some(level);
+        self
+    }
+
+    pub fn with_window_origin_mode(mut self, mode: WindowOriginMode) -> Self {
+        self.window_origin_mode = Some(mode);
+        self
+    }
+
+    pub fn with_depth_mode(mut self, mode: DepthMode) -> Self {
+        self.depth_mode = Some(mode);
+        self
+    }
+
+    pub fn finish(self) -> Result<Device, DeviceError> {
+        Device::create(self)
+    }
+}
+
+impl Deref for DeviceBuilder {
+    type Target = super::DeviceBuilder;
+
+    fn deref(&self) -> &Self::Target {
+        &self.builder
+    }
+}
+
+impl DerefMut for DeviceBuilder {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.builder
+    }
+}
+
+/// An owned device. The first device created is also the one procs get resolved through.
+pub struct Device {
+    // boxed so the address handed to other objects and the proc resolver stays put
+    device: Box<super::Device>
+}
+
+impl Device {
+    pub fn new() -> DeviceBuilder {
+        DeviceBuilder::new()
+    }
+
+    pub fn create(mut builder: DeviceBuilder) -> Result<Self, DeviceError> {
+        let mut flags = builder.flags;
+        if let Some(level) = builder.debug_level {
+            flags.set_debug_enable(true);
+            match level {
+                0 => flags.set_debug_enable_level_0(true),
+                1 => flags.set_debug_enable_level_1(true),
+                2 => flags.set_debug_enable_level_2(true),
+                3 => flags.set_debug_enable_level_3(true),
+                4 => flags.set_debug_enable_level_4(true),
+                _ => return Err(DeviceError::InvalidDebugLevel(level))
+            }
+        }
+        builder.set_flags(flags);
+
+        let mut device = Box::new(super::Device::new());
+        if !bootstrap_device_init(&mut device, builder.deref()).map_err(DeviceError::UnresolvedProc)? {
+            return Err(DeviceError::InitializeFailed);
+        }
+
+        register_proc_device(device.as_ref());
+
+        if let Some(mode) = builder.window_origin_mode {
+            device.set_window_origin_mode(mode);
+        }
+
+        if let Some(mode) = builder.depth_mode {
+            device.set_depth_mode(mode);
+        }
+
+        Ok(Self {
+            device
+        })
+    }
+
+    pub fn as_ref(&self) -> &super::Device {
+        &self.device
+    }
+
+    pub fn as_mut(&mut self) -> &mut super::Device {
+        &mut self.device
+    }
+}
+
+impl Deref for Device {
+    type Target = super::Device;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+impl DerefMut for Device {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut()
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        unregister_proc_device(self.device.as_ref());
+        self.device.fini();
+    }
+}
+
 pub struct MemPoolBuilder {
     builder: MemoryPoolBuilder,
-    shared: bool
+    storage: Option<AlignedStorage>,
+    allocator: Arc<dyn HostAllocator>,
+    failed_alloc: Option<usize>
 }
 
 impl MemPoolBuilder {
@@ -36,11 +258,13 @@ impl MemPoolBuilder {
                 ret.set_defaults();
                 ret
             },
-            shared: false
+            storage: None,
+            allocator: default_allocator(),
+            failed_alloc: None
         }
     }
 
-    pub fn with_device(mut self, device: *const Device) -> Self {
+    pub fn with_device(mut self, device: *const super::Device) -> Self {
         self.set_device(device);
         self
     }
@@ -50,53 +274,95 @@ impl MemPoolBuilder {
         self
     }
 
-    pub fn with_storage(mut self, memory: *const u8, size: usize) -> Self {
-        if !self.get_memory().is_null() && !self.shared {
-            unsafe {
-                libc::free(self.get_memory() as *mut c_void);
-            }
-        }
-        self.set_storage(memory, size);
-        self.shared = false;
+    /// Allocator `make_storage` takes memory from.
+    pub fn with_allocator(mut self, allocator: Arc<dyn HostAllocator>) -> Self {
+        self.allocator = allocator;
         self
     }
 
-    pub fn with_shared_storage(mut self, memory: *const u8, size: usize) -> Self {
-        if !self.get_memory().is_null() && !self.shared {
-            unsafe {
-                libc::free(self.get_memory() as *mut c_void);
-            }
-        }
-        self.set_storage(memory, size);
-        self.shared = true;
+    pub fn with_aligned_storage(mut self, storage: AlignedStorage) -> Self {
+        self.set_storage(storage.as_ptr(), storage.size());
+        self.storage = Some(storage);
         self
     }
 
-    #[track_caller]
-    pub fn make_storage(self, size: usize, align: Option<usize>) -> Self {
-        let memory = if let Some(align) = align {
-            unsafe {
-                libc::memalign(align, size)
-            }
-        } else {
-            unsafe {
-                libc::memalign(0x1000, size)
+    /// Takes ownership of `memory`, which is freed when the pool is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `memory` has to be a block of `size` bytes returned by `memalign` with an alignment of at
+    /// least `MEMORY_POOL_GRANULARITY`, and must not be used or freed by anything else afterwards.
+    pub unsafe fn with_storage(self, memory: *const u8, size: usize) -> Self {
+        let storage = AlignedStorage::from_raw(memory as *mut u8, size, MEMORY_POOL_GRANULARITY, Arc::new(LibcAllocator));
+        self.with_aligned_storage(storage)
+    }
+
+    /// Uses `memory` without taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `memory` has to point to `size` bytes aligned to `MEMORY_POOL_GRANULARITY`, which stay valid
+    /// and aren't used for anything else until the pool is dropped.
+    pub unsafe fn with_shared_storage(self, memory: *const u8, size: usize) -> Self {
+        let storage = AlignedStorage::borrowed(memory as *mut u8, size);
+        self.with_aligned_storage(storage)
+    }
+
+    /// Allocates the storage from the builder's allocator. Failing to is reported by `validate`.
+    pub fn make_storage(mut self, size: usize, align: Option<usize>) -> Self {
+        match AlignedStorage::alloc(self.allocator.clone(), size, align.unwrap_or(MEMORY_POOL_GRANULARITY)) {
+            Some(storage) => self.with_aligned_storage(storage),
+            None => {
+                self.failed_alloc = Some(size);
+                self
             }
-        };
+        }
+    }
 
-        if memory.is_null() {
-            panic!("MemPoolBuilder unable to allocate storage");
+    pub fn validate(&self) -> Result<(), BuildError> {
+        if self.get_device().is_null() {
+            return Err(BuildError::MissingDevice);
         }
 
-        self.with_storage(memory as *const u8, size)
-    }
+        if let Some(size) = self.failed_alloc {
+            return Err(BuildError::AllocationFailed {
+                param: "storage",
+                size
+            });
+        }
 
-    pub fn create(&self) -> Result<MemPool, InitializeError> {
-        MemPool::create(self)
+        let memory = self.get_memory() as usize;
+        if memory == 0 {
+            return Err(BuildError::NullMemory("storage"));
+        }
+
+        if !memory.is_multiple_of(MEMORY_POOL_GRANULARITY) {
+            return Err(BuildError::Misaligned {
+                param: "storage",
+                address: memory,
+                align: MEMORY_POOL_GRANULARITY
+            });
+        }
+
+        let size = self.get_size();
+        if size == 0 || !size.is_multiple_of(MEMORY_POOL_GRANULARITY) {
+            return Err(BuildError::InvalidSize {
+                param: "storage",
+                size,
+                granularity: MEMORY_POOL_GRANULARITY
+            });
+        }
+
+        let flags = self.get_flags();
+        if flags.cpu_no_access() && flags.cpu_cached() {
+            return Err(BuildError::ConflictingFlags(flags));
+        }
+
+        Ok(())
     }
 
-    pub fn finish(self) -> Result<MemPool, InitializeError> {
-        MemPool::create(&self)
+    pub fn finish(self) -> Result<MemPool, BuildError> {
+        MemPool::create(self)
     }
 }
 
@@ -117,8 +383,7 @@ impl DerefMut for MemPoolBuilder {
 pub struct MemPool {
     pool: MemoryPool,
     offset: AtomicUsize,
-    shared: bool,
-    memory: *mut u8
+    storage: Option<AlignedStorage>
 }
 
 #[derive(Error, Debug)]
@@ -129,45 +394,184 @@ pub enum MemPoolError {
     OutOfMemory,
     #[error("Memory is null")]
     NullMemory,
+    #[error("Alignment {0:#x} is not a power of two")]
+    InvalidAlignment(usize),
+    #[error("Ring allocator is full until an earlier frame's fence signals")]
+    RingExhausted,
+    #[error("Failed to create a memory pool")]
+    PoolCreationFailed(#[source] BuildError),
+    #[error("Memory can't be viewed as the requested type: {0:?}")]
+    InvalidCast(bytemuck::PodCastError),
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceError {
+    #[error("Unable to resolve {0}")]
+    UnresolvedProc(&'static str),
+    #[error("nvnDeviceInitialize failed")]
+    InitializeFailed,
+    #[error("Debug layer level {0} does not exist")]
+    InvalidDebugLevel(u8),
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueError {
+    #[error("Unknown GPU error")]
+    Unknown,
+    #[error("GPU MMU fault on {access:?} access at {address:#x}")]
+    MmuFault {
+        address: u64,
+        access: MmuFaultAccessType
+    },
+    #[error("GPU push buffer DMA exception")]
+    PbdmaException,
+    #[error("GPU engine exception")]
+    EngineException,
+    #[error("GPU timeout")]
+    Timeout,
+}
+
+#[derive(Error, Debug)]
+pub enum CopyError {
+    #[error("Source region {0:?} does not fit in the source texture")]
+    SourceOutOfBounds(CopyRegion),
+    #[error("Destination region {0:?} does not fit in the destination texture")]
+    DestinationOutOfBounds(CopyRegion),
+    #[error("Source region {0:?} and destination region {1:?} differ in size")]
+    SizeMismatch(CopyRegion, CopyRegion),
+}
+
+#[derive(Error, Debug)]
+pub enum UploadError {
+    #[error(transparent)]
+    Staging(#[from] MemPoolError),
+    #[error(transparent)]
+    Copy(#[from] CopyError),
+    #[error(transparent)]
+    Commands(#[from] CommandMemoryError),
+    #[error("Range {0:?} does not fit in the destination buffer of {1:#x} bytes")]
+    BufferOutOfBounds(Range<usize>, usize),
+    #[error("Texture level {0} does not exist")]
+    InvalidLevel(i32),
+    #[error("Region needs {expected:#x} bytes of data, {actual:#x} were given")]
+    DataSizeMismatch {
+        expected: usize,
+        actual: usize
+    },
 }
 
-pub struct GpuMemory<'a> {
-    pool: &'a MemPool,
+/// A range reserved from a `MemPool`, kept alive by `P`.
+pub struct PoolMemory<P: Deref<Target = MemPool>> {
+    pool: P,
     range: Range<usize>
 }
 
-impl<'a> GpuMemory<'a> {
-    pub fn cpu(&self) -> Result<&'a [u8], MemPoolError> {
-        if self.pool.get_flags().cpu_no_access() {
-            Err(MemPoolError::NoCPUAccess)
-        } else {
-            let mem = self.pool.map();
-            if mem.is_null() {
-                Err(MemPoolError::NullMemory)
-            } else {
-                Ok(unsafe {
-                    self.cpu_unchecked()
-                })
-            }
+pub type GpuMemory<'a> = PoolMemory<&'a MemPool>;
+
+/// Memory that keeps its pool alive, so it can be stored anywhere.
+pub type SharedGpuMemory = PoolMemory<Arc<MemPool>>;
+
+impl<P: Deref<Target = MemPool>> PoolMemory<P> {
+    /// CPU view of this allocation, and of nothing else in the pool.
+    pub fn cpu(&self) -> Result<&[u8], MemPoolError> {
+        self.check_cpu()?;
+        Ok(unsafe {
+            self.cpu_unchecked()
+        })
+    }
+
+    pub fn cpu_mut(&mut self) -> Result<&mut [u8], MemPoolError> {
+        self.check_cpu()?;
+        Ok(unsafe {
+            self.cpu_unchecked_mut()
+        })
+    }
+
+    /// `cpu` without the checks.
+    ///
+    /// # Safety
+    ///
+    /// The pool has to be CPU accessible and mapped, and the GPU must not write to the allocation
+    /// while the slice is alive.
+    pub unsafe fn cpu_unchecked(&self) -> &[u8] {
+        std::slice::from_raw_parts(self.pool.map().add(self.range.start), self.len())
+    }
+
+    /// `cpu_mut` without the checks.
+    ///
+    /// # Safety
+    ///
+    /// The pool has to be CPU accessible and mapped, and the GPU must not access the allocation
+    /// while the slice is alive.
+    pub unsafe fn cpu_unchecked_mut(&mut self) -> &mut [u8] {
+        std::slice::from_raw_parts_mut(self.pool.map().add(self.range.start), self.len())
+    }
+
+    /// Views the allocation as `T`s. It has to start at a multiple of `T`'s alignment and hold a
+    /// whole number of them.
+    pub fn as_slice<T: Pod>(&self) -> Result<&[T], MemPoolError> {
+        bytemuck::try_cast_slice(self.cpu()?).map_err(MemPoolError::InvalidCast)
+    }
+
+    pub fn as_mut_slice<T: Pod>(&mut self) -> Result<&mut [T], MemPoolError> {
+        bytemuck::try_cast_slice_mut(self.cpu_mut()?).map_err(MemPoolError::InvalidCast)
+    }
+
+    /// Makes CPU writes to this allocation visible to the GPU. Done on drop as well. Does nothing
+    /// if the pool isn't CPU accessible.
+    pub fn flush(&self) {
+        if self.check_cpu().is_ok() {
+            self.pool.flush(self.range.start, self.len())
         }
     }
 
-    pub unsafe fn cpu_unchecked(&self) -> &'a [u8] {
-        std::slice::from_raw_parts(self.pool.map(), self.pool.get_size())
+    /// Discards stale CPU cache lines so GPU writes to this allocation can be read back. Does
+    /// nothing if the pool isn't CPU accessible.
+    pub fn invalidate(&self) {
+        if self.check_cpu().is_ok() {
+            self.pool.invalidate(self.range.start, self.len())
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.range.end - self.range.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.range.start == self.range.end
     }
 
     pub fn range(&self) -> Range<usize> {
         self.range.clone()
     }
 
+    /// GPU address of the start of this allocation, usable for vertex and index buffers.
+    pub fn address(&self) -> BufferAddress {
+        self.pool.get_buffer_address().offset(self.range.start as u64)
+    }
+
+    pub fn pool(&self) -> &MemPool {
+        &self.pool
+    }
+
     pub fn into_range(self) -> Range<usize> {
         self.range.clone()
     }
+
+    fn check_cpu(&self) -> Result<(), MemPoolError> {
+        if self.pool.get_flags().cpu_no_access() {
+            Err(MemPoolError::NoCPUAccess)
+        } else if self.pool.map().is_null() {
+            Err(MemPoolError::NullMemory)
+        } else {
+            Ok(())
+        }
+    }
 }
 
-impl<'a> Drop for GpuMemory<'a> {
+impl<P: Deref<Target = MemPool>> Drop for PoolMemory<P> {
     fn drop(&mut self) {
-        self.pool.flush(self.range.start, self.range.end)
+        self.flush()
     }
 }
 
@@ -178,29 +582,55 @@ impl MemPool {
         MemPoolBuilder::new()
     }
 
-    pub fn create(builder: &MemPoolBuilder) -> Result<Self, InitializeError> {
+    pub fn create(builder: MemPoolBuilder) -> Result<Self, BuildError> {
+        builder.validate()?;
+
         let mut pool = MemoryPool::default();
         if pool.initialize(builder.deref()) {
             Ok(Self {
                 pool,
                 offset: AtomicUsize::new(0),
-                shared: builder.shared,
-                memory: builder.get_memory()
+                storage: builder.storage
             })
         } else {
-            Err(Self::get_err())
+            Err(Self::get_err().into())
         }
     }
 
-    pub fn reserve_mem(&mut self, size: usize) -> Result<GpuMemory, MemPoolError> {
-        if self.offset.load(Ordering::SeqCst) + size > self.get_size() {
-            Err(MemPoolError::OutOfMemory)
-        } else {
-            let start = self.offset.fetch_add(size, Ordering::SeqCst);
-            Ok(GpuMemory {
-                pool: self,
-                range: (start..start + size)
-            })
+    pub fn reserve_mem(&self, size: usize) -> Result<GpuMemory<'_>, MemPoolError> {
+        self.reserve_mem_aligned(size, 1)
+    }
+
+    pub fn reserve_mem_aligned(&self, size: usize, align: usize) -> Result<GpuMemory<'_>, MemPoolError> {
+        Ok(GpuMemory {
+            range: self.bump(size, align)?,
+            pool: self
+        })
+    }
+
+    pub fn into_shared(self) -> SharedMemPool {
+        SharedMemPool(Arc::new(self))
+    }
+
+    /// Reserves the next `size` bytes at a multiple of `align`. Reservations are never given
+    /// back, which is what lets concurrent callers get away with a single atomic.
+    fn bump(&self, size: usize, align: usize) -> Result<Range<usize>, MemPoolError> {
+        if !align.is_power_of_two() {
+            return Err(MemPoolError::InvalidAlignment(align));
+        }
+
+        let mut offset = self.offset.load(Ordering::Relaxed);
+        loop {
+            let start = offset.checked_add(align - 1).ok_or(MemPoolError::OutOfMemory)? & !(align - 1);
+            let end = start.checked_add(size).ok_or(MemPoolError::OutOfMemory)?;
+            if end > self.get_size() {
+                return Err(MemPoolError::OutOfMemory);
+            }
+
+            match self.offset.compare_exchange_weak(offset, end, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return Ok(start..end),
+                Err(current) => offset = current
+            }
         }
     }
 
@@ -230,156 +660,827 @@ impl DerefMut for MemPool {
 impl Drop for MemPool {
     fn drop(&mut self) {
         self.finalize();
-        if !self.shared {
-            unsafe {
-                libc::free(self.memory as *mut c_void);
-            }
-        }
     }
 }
 
-pub struct CommandBufferBuilder {
-    device: *mut Device,
-    control: (*mut u8, usize),
-    shared: bool,
-    pool: *mut MemoryPool,
-    command: (usize, usize)
+/// Reference counted `MemPool`. Memory reserved through it holds a reference as well, so the pool
+/// is only finalized once the handle and every reservation are gone.
+#[derive(Clone)]
+pub struct SharedMemPool(Arc<MemPool>);
+
+impl SharedMemPool {
+    pub fn reserve_mem(&self, size: usize) -> Result<SharedGpuMemory, MemPoolError> {
+        self.reserve_mem_aligned(size, 1)
+    }
+
+    pub fn reserve_mem_aligned(&self, size: usize, align: usize) -> Result<SharedGpuMemory, MemPoolError> {
+        Ok(SharedGpuMemory {
+            range: self.0.bump(size, align)?,
+            pool: self.0.clone()
+        })
+    }
 }
 
-impl CommandBufferBuilder {
-    pub fn new() -> Self {
+impl Deref for SharedMemPool {
+    type Target = MemPool;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// General purpose allocator over a `MemPool`, handing out aligned ranges that return to the
+/// pool when their `Allocation` is dropped.
+pub struct SubAllocator {
+    pool: MemPool,
+    ranges: Mutex<RangeAllocator>
+}
+
+impl SubAllocator {
+    pub fn new(pool: MemPool) -> Self {
+        let size = pool.get_size();
         Self {
-            device: 0 as _,
-            control: (0 as _, 0),
-            shared: false,
-            pool: 0 as _,
-            command: (0, 0)
+            pool,
+            ranges: Mutex::new(RangeAllocator::new(size))
         }
     }
 
-    pub fn with_device(mut self, device: *mut Device) -> Self {
-        self.device = device;
-        self
+    pub fn alloc(&self, size: usize, align: usize) -> Result<Allocation<'_>, MemPoolError> {
+        if !align.is_power_of_two() {
+            return Err(MemPoolError::InvalidAlignment(align));
+        }
+
+        let range = self.ranges.lock().unwrap().alloc(size, align).ok_or(MemPoolError::OutOfMemory)?;
+        Ok(Allocation {
+            allocator: self,
+            range
+        })
     }
 
-    pub fn with_control(mut self, memory: *mut u8, size: usize) -> Self {
-        if !self.control.0.is_null() && !self.shared {
-            unsafe {
-                libc::free(self.control.0 as *mut c_void);
-            }
-        }
-        self.control = (memory, size);
-        self.shared = false;
-        self
+    pub fn free(&self, allocation: Allocation) {
+        drop(allocation);
     }
 
-    pub fn with_shared_control(mut self, memory: *mut u8, size: usize) -> Self {
-        if !self.control.0.is_null() && !self.shared {
-            unsafe {
-                libc::free(self.control.0 as *mut c_void);
-            }
-        }
-        self.control = (memory, size);
-        self.shared = true;
-        self
+    pub fn stats(&self) -> AllocatorStats {
+        self.ranges.lock().unwrap().stats()
     }
 
-    #[track_caller]
-    pub fn make_control(self, size: usize, align: Option<usize>) -> Self {
-        if !self.control.0.is_null() && !self.shared {
-            unsafe {
-                libc::free(self.control.0 as *mut c_void);
-            }
-        }
+    pub fn pool(&self) -> &MemPool {
+        &self.pool
+    }
 
-        let control = if let Some(align) = align {
-            unsafe {
-                libc::memalign(align, size)
-            }
-        } else {
-            unsafe {
-                libc::memalign(0x1000, size)
-            }
-        };
+    pub fn into_pool(self) -> MemPool {
+        self.pool
+    }
+}
 
-        if control.is_null() {
-            panic!("CommandBufferBuilder unable to allocate storage!");
-        }
+pub struct Allocation<'a> {
+    allocator: &'a SubAllocator,
+    range: Range<usize>
+}
 
-        self.with_control(control as *mut u8, size)
+impl<'a> Allocation<'a> {
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
     }
 
-    pub fn with_command(mut self, memory_pool: *mut MemoryPool, start: usize, size: usize) -> Self {
-        self.pool = memory_pool;
-        self.command = (start, size);
-        self
+    pub fn size(&self) -> usize {
+        self.range.end - self.range.start
     }
 
-    pub fn finish(self) -> Result<CommandBuffer, InitializeError> {
-        CommandBuffer::create(self)
+    pub fn address(&self) -> BufferAddress {
+        self.allocator.pool.get_buffer_address().offset(self.range.start as u64)
+    }
+
+    pub fn pool(&self) -> &'a MemPool {
+        &self.allocator.pool
     }
 }
 
-impl Drop for CommandBufferBuilder {
+impl<'a> Drop for Allocation<'a> {
     fn drop(&mut self) {
-        if !self.shared {
-            unsafe {
-                libc::free(self.control.0 as *mut libc::c_void);
-            }
-        }
+        self.allocator.ranges.lock().unwrap().free(self.range.clone());
     }
 }
 
-pub struct CommandBuffer {
-    buffer: super::CommandBuffer,
-    control: (*mut u8, usize),
-    shared: bool,
-    command: (usize, usize),
-    pool: *mut MemoryPool
+/// A set of `MemPool`s grouped by their `MemoryPoolFlags`, grown one block at a time as
+/// allocations need more room.
+pub struct GpuHeap {
+    device: *mut super::Device,
+    block_size: usize,
+    blocks: Mutex<Vec<(u32, Box<SubAllocator>)>>
 }
 
-impl_init_err!(CommandBuffer);
+impl GpuHeap {
+    /// New pools are `block_size` bytes, rounded up to the pool granularity. Allocations larger
+    /// than that get a pool of their own.
+    pub fn new(device: *mut super::Device, block_size: usize) -> Self {
+        Self {
+            device,
+            block_size: align_up(block_size.max(1), MEMORY_POOL_GRANULARITY),
+            blocks: Mutex::new(Vec::new())
+        }
+    }
 
-impl CommandBuffer {
-    pub fn new() -> CommandBufferBuilder {
-        CommandBufferBuilder::new()
+    pub fn alloc(&self, size: usize, align: usize, flags: MemoryPoolFlags) -> Result<Allocation<'_>, MemPoolError> {
+        if !align.is_power_of_two() {
+            return Err(MemPoolError::InvalidAlignment(align));
+        }
+
+        let key = u32::from(flags);
+        let mut blocks = self.blocks.lock().unwrap();
+
+        for (block_key, block) in blocks.iter() {
+            if *block_key == key {
+                if let Ok(allocation) = self.extend(block).alloc(size, align) {
+                    return Ok(allocation);
+                }
+            }
+        }
+
+        let pool_size = align_up(size.max(self.block_size), MEMORY_POOL_GRANULARITY);
+        let pool = MemPool::new()
+            .with_device(self.device)
+            .with_flags(flags)
+            .make_storage(pool_size, None)
+            .finish()
+            .map_err(MemPoolError::PoolCreationFailed)?;
+
+        blocks.push((key, Box::new(SubAllocator::new(pool))));
+        let (_, block) = blocks.last().unwrap();
+        self.extend(block).alloc(size, align)
     }
 
-    pub fn create(builder: CommandBufferBuilder) -> Result<Self, InitializeError> {
-        let CommandBufferBuilder { device, control, shared, pool, command } = builder;
+    /// Frees every pool no allocation lives in anymore. The GPU must be done with them.
+    pub fn release_empty(&self) {
+        self.blocks.lock().unwrap().retain(|(_, block)| block.stats().allocations != 0);
+    }
+
+    pub fn pool_count(&self) -> usize {
+        self.blocks.lock().unwrap().len()
+    }
+
+    /// Usage of all the pools combined.
+    pub fn stats(&self) -> AllocatorStats {
+        self.blocks.lock().unwrap().iter().fold(AllocatorStats::default(), |total, (_, block)| {
+            let stats = block.stats();
+            AllocatorStats {
+                size: total.size + stats.size,
+                used: total.used + stats.used,
+                allocations: total.allocations + stats.allocations,
+                free_blocks: total.free_blocks + stats.free_blocks,
+                largest_free_block: total.largest_free_block.max(stats.largest_free_block)
+            }
+        })
+    }
+
+    /// Blocks are boxed and only dropped by `release_empty` once nothing is allocated from them,
+    /// so they outlive every allocation tied to the heap's lifetime.
+    fn extend<'a>(&'a self, block: &SubAllocator) -> &'a SubAllocator {
+        unsafe {
+            &*(block as *const SubAllocator)
+        }
+    }
+}
+
+/// Transient memory handed out by `RingAllocator::alloc`. It stays valid until the fence of the
+/// frame it was allocated in signals, and borrows the allocator so it can't be written to after
+/// the frame has ended or the pool is gone. Any number of allocations can be live at once, ending
+/// the frame waits for all of them to be dropped.
+pub struct RingAllocation<'a> {
+    range: Range<usize>,
+    address: BufferAddress,
+    memory: *mut u8,
+    _ring: PhantomData<&'a RingAllocator>
+}
+
+impl<'a> RingAllocation<'a> {
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    pub fn address(&self) -> BufferAddress {
+        self.address
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe {
+            std::slice::from_raw_parts_mut(self.memory, self.range.end - self.range.start)
+        }
+    }
+
+    #[track_caller]
+    pub fn write<T: Copy>(&mut self, value: &T) {
+        let size = std::mem::size_of::<T>();
+        assert!(size <= self.range.end - self.range.start, "Value does not fit in the ring allocation");
+        unsafe {
+            std::ptr::copy_nonoverlapping(value as *const T as *const u8, self.memory, size);
+        }
+    }
+}
+
+/// Per-frame allocator over a CPU visible `MemPool` for data the GPU only reads once, like
+/// uniforms and dynamic vertices. Space used by a frame is only reused once the fence placed by
+/// `end_frame` signals.
+pub struct RingAllocator {
+    pool: MemPool,
+    state: Mutex<RingState>
+}
+
+struct RingState {
+    ranges: RingRanges,
+    frame_start: usize,
+    frames: FencedFrames<RingFrame>
+}
+
+impl RingState {
+    fn reclaim(&mut self) {
+        while let Some(frame) = self.frames.pop_signaled() {
+            self.ranges.release(frame);
+        }
+    }
+}
+
+impl RingAllocator {
+    pub fn new(pool: MemPool, device: *mut super::Device) -> Result<Self, MemPoolError> {
+        if pool.get_flags().cpu_no_access() {
+            return Err(MemPoolError::NoCPUAccess);
+        }
+
+        if pool.map().is_null() {
+            return Err(MemPoolError::NullMemory);
+        }
+
+        let size = pool.get_size();
+        Ok(Self {
+            pool,
+            state: Mutex::new(RingState {
+                ranges: RingRanges::new(size),
+                frame_start: 0,
+                frames: FencedFrames::new(device)
+            })
+        })
+    }
+
+    pub fn alloc(&self, size: usize, align: usize) -> Result<RingAllocation<'_>, MemPoolError> {
+        if !align.is_power_of_two() {
+            return Err(MemPoolError::InvalidAlignment(align));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let range = match state.ranges.alloc(size, align) {
+            Some(range) => range,
+            None => {
+                state.reclaim();
+                state.ranges.alloc(size, align).ok_or(MemPoolError::RingExhausted)?
+            }
+        };
+
+        Ok(RingAllocation {
+            address: self.pool.get_buffer_address().offset(range.start as u64),
+            memory: unsafe { self.pool.map().add(range.start) },
+            range,
+            _ring: PhantomData
+        })
+    }
+
+    /// Makes what was written during the current frame visible to the GPU. Has to happen before
+    /// commands reading it are submitted, `end_frame` only catches what was written since.
+    pub fn flush(&mut self) {
+        if self.pool.get_flags().cpu_cached() {
+            self.flush_frame();
+        }
+    }
+
+    /// Closes the current frame by fencing `queue`. Everything allocated since the previous call
+    /// is reclaimed once the GPU reaches the returned fence.
+    pub fn end_frame(&mut self, queue: &mut super::Queue) -> Result<Arc<Fence>, InitializeError> {
+        let fence = self.state.get_mut().unwrap().frames.next_fence()?;
+        self.flush();
+
+        let state = self.state.get_mut().unwrap();
+        let frame = state.ranges.end_frame();
+        let fence = state.frames.push(queue, fence, frame);
+        state.reclaim();
+        Ok(fence)
+    }
+
+    /// Releases every frame whose fence has signaled.
+    pub fn reclaim(&mut self) {
+        self.state.get_mut().unwrap().reclaim();
+    }
+
+    pub fn used(&self) -> usize {
+        self.state.lock().unwrap().ranges.used()
+    }
+
+    pub fn pool(&self) -> &MemPool {
+        &self.pool
+    }
+
+    fn flush_frame(&mut self) {
+        let state = self.state.get_mut().unwrap();
+        let head = state.ranges.head();
+        if state.frame_start <= head {
+            self.pool.flush(state.frame_start, head - state.frame_start);
+        } else {
+            self.pool.flush(state.frame_start, state.ranges.size() - state.frame_start);
+            self.pool.flush(0, head);
+        }
+        state.frame_start = head;
+    }
+}
+
+pub struct CommandBufferBuilder {
+    device: *mut super::Device,
+    control: Option<AlignedStorage>,
+    allocator: Arc<dyn HostAllocator>,
+    pool: *mut MemoryPool,
+    command: (usize, usize),
+    growth_pool: *mut MemoryPool,
+    growth: (usize, usize),
+    chunk_sizes: (usize, usize),
+    failed_alloc: Option<usize>
+}
+
+impl CommandBufferBuilder {
+    pub fn new() -> Self {
+        Self {
+            device: 0 as _,
+            control: None,
+            allocator: default_allocator(),
+            pool: 0 as _,
+            command: (0, 0),
+            growth_pool: 0 as _,
+            growth: (0, 0),
+            chunk_sizes: (0, 0),
+            failed_alloc: None
+        }
+    }
+
+    pub fn with_device(mut self, device: *mut super::Device) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// Allocator `make_control` and command memory growth take control memory from.
+    pub fn with_allocator(mut self, allocator: Arc<dyn HostAllocator>) -> Self {
+        self.allocator = allocator;
+        self
+    }
+
+    pub fn with_control_storage(mut self, storage: AlignedStorage) -> Self {
+        self.control = Some(storage);
+        self
+    }
+
+    /// Takes ownership of `memory`, which is freed when the command buffer is dropped.
+    ///
+    /// # Safety
+    ///
+    /// `memory` has to be a block of `size` bytes returned by `memalign`, aligned to the device's
+    /// control memory alignment, and must not be used or freed by anything else afterwards.
+    pub unsafe fn with_control(self, memory: *mut u8, size: usize) -> Self {
+        let storage = AlignedStorage::from_raw(memory, size, 1, Arc::new(LibcAllocator));
+        self.with_control_storage(storage)
+    }
+
+    /// Uses `memory` as control memory without taking ownership of it.
+    ///
+    /// # Safety
+    ///
+    /// `memory` has to point to `size` bytes aligned to the device's control memory alignment,
+    /// which stay valid and aren't used for anything else until the command buffer is dropped.
+    pub unsafe fn with_shared_control(self, memory: *mut u8, size: usize) -> Self {
+        let storage = AlignedStorage::borrowed(memory, size);
+        self.with_control_storage(storage)
+    }
+
+    /// Allocates control memory from the builder's allocator. Failing to is reported by
+    /// `validate`.
+    pub fn make_control(mut self, size: usize, align: Option<usize>) -> Self {
+        match AlignedStorage::alloc(self.allocator.clone(), size, align.unwrap_or(MEMORY_POOL_GRANULARITY)) {
+            Some(storage) => self.with_control_storage(storage),
+            None => {
+                self.failed_alloc = Some(size);
+                self
+            }
+        }
+    }
+
+    /// Records commands into `start..start + size` of `memory_pool`.
+    ///
+    /// # Safety
+    ///
+    /// `memory_pool` has to be a pool of the same device that outlives the command buffer. The
+    /// range has to lie within it, be aligned to the device's command memory alignment and not be
+    /// written to by anything else while commands recorded into it may still be executed.
+    pub unsafe fn with_command(mut self, memory_pool: *mut MemoryPool, start: usize, size: usize) -> Self {
+        self.pool = memory_pool;
+        self.command = (start, size);
+        self
+    }
+
+    /// Lets the command buffer grow past the memory given to `with_command` and `with_control`.
+    /// Extra command memory is carved out of `start..start + size` in `memory_pool`, and extra
+    /// control memory is allocated on the heap, whenever the driver runs out of either. The last
+    /// command chunk of the range and one control chunk are held back, so a recording that runs
+    /// out can still be ended and reported by `Recording::finish`.
+    ///
+    /// # Safety
+    ///
+    /// Same as for `with_command`, for the growth range.
+    pub unsafe fn with_command_growth(mut self, memory_pool: *mut MemoryPool, start: usize, size: usize) -> Self {
+        self.growth_pool = memory_pool;
+        self.growth = (start, size);
+        self
+    }
+
+    /// Minimum sizes of the command and control chunks added on growth. Defaults to the device's
+    /// minimum command and control memory sizes.
+    pub fn with_growth_chunk_sizes(mut self, command: usize, control: usize) -> Self {
+        self.chunk_sizes = (command, control);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), BuildError> {
+        if self.device.is_null() {
+            return Err(BuildError::MissingDevice);
+        }
+
+        if let Some(size) = self.failed_alloc {
+            return Err(BuildError::AllocationFailed {
+                param: "control",
+                size
+            });
+        }
+
+        let control = match self.control.as_ref() {
+            Some(control) if !control.as_ptr().is_null() => control,
+            _ => return Err(BuildError::NullMemory("control"))
+        };
+
+        let (min_size, align) = unsafe {
+            (
+                (*self.device).get_info(DeviceInfo::CommandBufferMinControlSize) as usize,
+                (*self.device).get_info(DeviceInfo::CommandBufferControlAlignment) as usize
+            )
+        };
+
+        let address = control.as_ptr() as usize;
+        if align != 0 && !address.is_multiple_of(align) {
+            return Err(BuildError::Misaligned {
+                param: "control",
+                address,
+                align
+            });
+        }
+
+        if control.size() < min_size {
+            return Err(BuildError::TooSmall {
+                param: "control",
+                size: control.size(),
+                min: min_size
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<CommandBuffer, BuildError> {
+        CommandBuffer::create(self)
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandMemoryError {
+    #[error("Ran out of command memory to grow into, {0:#x} more bytes were needed")]
+    OutOfCommandMemory(usize),
+    #[error("Failed to allocate {0:#x} bytes of control memory")]
+    OutOfControlMemory(usize),
+}
+
+/// Memory a command buffer grows into. One chunk of each kind is held back, so that the memory
+/// callback can always add what the driver asks for: once growing fails the reserve keeps the
+/// buffer going until `Recording::finish` reports the failure.
+struct CommandMemoryGrowth {
+    pool: *mut MemoryPool,
+    range: Range<usize>,
+    offset: usize,
+    command_chunk: (usize, usize),
+    control_chunk: (usize, usize),
+    control_chunks: Vec<AlignedStorage>,
+    allocator: Arc<dyn HostAllocator>,
+    command_reserve: Range<usize>,
+    command_reserve_used: bool,
+    control_reserve: AlignedStorage,
+    control_reserve_used: bool,
+    failed: Option<CommandMemoryError>
+}
+
+impl CommandMemoryGrowth {
+    fn new(device: *mut super::Device, pool: *mut MemoryPool, growth: (usize, usize), chunk_sizes: (usize, usize), allocator: Arc<dyn HostAllocator>) -> Result<Self, BuildError> {
+        let (command_size, command_align, control_size, control_align) = unsafe {
+            (
+                (*device).get_info(DeviceInfo::CommandBufferMinCommandSize) as usize,
+                (*device).get_info(DeviceInfo::CommandBufferCommandAlignment) as usize,
+                (*device).get_info(DeviceInfo::CommandBufferMinControlSize) as usize,
+                (*device).get_info(DeviceInfo::CommandBufferControlAlignment) as usize
+            )
+        };
+
+        let command_chunk = (chunk_sizes.0.max(command_size), command_align.max(1));
+        let control_chunk = (chunk_sizes.1.max(control_size), control_align.max(1));
+
+        // the command reserve is the last aligned chunk of the growth range
+        let end = growth.0 + growth.1;
+        let reserve_start = end.checked_sub(command_chunk.0).map(|start| start & !(command_chunk.1 - 1));
+        let command_reserve = match reserve_start {
+            Some(start) if start >= growth.0 => start..start + command_chunk.0,
+            _ => return Err(BuildError::TooSmall {
+                param: "growth",
+                size: growth.1,
+                min: command_chunk.0
+            })
+        };
+
+        let control_reserve = AlignedStorage::alloc(allocator.clone(), control_chunk.0, control_chunk.1)
+            .ok_or(BuildError::AllocationFailed {
+                param: "growth",
+                size: control_chunk.0
+            })?;
+
+        Ok(Self {
+            pool,
+            range: growth.0..command_reserve.start,
+            offset: growth.0,
+            command_chunk,
+            control_chunk,
+            control_chunks: Vec::new(),
+            allocator,
+            command_reserve,
+            command_reserve_used: false,
+            control_reserve,
+            control_reserve_used: false,
+            failed: None
+        })
+    }
+
+    fn add_command(&mut self, buffer: &mut super::CommandBuffer, min_size: usize) -> Result<(), CommandMemoryError> {
+        let (chunk_size, align) = self.command_chunk;
+        let start = align_up(self.offset, align);
+        let size = align_up(min_size.max(chunk_size), align);
+
+        if start + size > self.range.end {
+            return Err(CommandMemoryError::OutOfCommandMemory(min_size));
+        }
+
+        self.offset = start + size;
+        buffer.add_command_memory(self.pool, start as u64, size);
+        Ok(())
+    }
+
+    fn add_control(&mut self, buffer: &mut super::CommandBuffer, min_size: usize) -> Result<(), CommandMemoryError> {
+        let (chunk_size, align) = self.control_chunk;
+        let size = align_up(min_size.max(chunk_size), align);
+        let chunk = AlignedStorage::alloc(self.allocator.clone(), size, align)
+            .ok_or(CommandMemoryError::OutOfControlMemory(size))?;
+
+        buffer.add_control_memory(chunk.as_ptr(), size);
+        self.control_chunks.push(chunk);
+        Ok(())
+    }
+
+    /// Hands the buffer the reserved chunk for `event`, returning false if it is used up or too
+    /// small for `min_size`.
+    fn add_reserve(&mut self, buffer: &mut super::CommandBuffer, event: CommandBufferMemoryEvent, min_size: usize) -> bool {
+        match event {
+            CommandBufferMemoryEvent::OutOfCommandMemory => {
+                let reserve = self.command_reserve.clone();
+                if self.command_reserve_used || min_size > reserve.len() {
+                    return false;
+                }
+
+                self.command_reserve_used = true;
+                buffer.add_command_memory(self.pool, reserve.start as u64, reserve.len());
+            },
+            CommandBufferMemoryEvent::OutOfControlMemory => {
+                if self.control_reserve_used || min_size > self.control_reserve.size() {
+                    return false;
+                }
+
+                self.control_reserve_used = true;
+                buffer.add_control_memory(self.control_reserve.as_ptr(), self.control_reserve.size());
+            }
+        }
+
+        true
+    }
+
+    fn reset(&mut self) {
+        self.offset = self.range.start;
+        self.control_chunks.clear();
+        self.command_reserve_used = false;
+        self.control_reserve_used = false;
+        self.failed = None;
+    }
+}
+
+extern "C" fn grow_command_memory(buffer: *mut super::CommandBuffer, event: CommandBufferMemoryEvent, min_size: usize, data: *mut c_void) {
+    let (buffer, growth) = unsafe {
+        (&mut *buffer, &mut *(data as *mut CommandMemoryGrowth))
+    };
+
+    let result = match event {
+        CommandBufferMemoryEvent::OutOfCommandMemory => growth.add_command(buffer, min_size),
+        CommandBufferMemoryEvent::OutOfControlMemory => growth.add_control(buffer, min_size)
+    };
+
+    // unwinding out of an extern "C" fn aborts, so the failure is kept for `Recording::finish`.
+    // The driver has to get at least `min_size` bytes either way, otherwise it writes past the
+    // end of the buffer's memory, which leaves nothing to do but abort once the reserve is gone.
+    if let Err(err) = result {
+        growth.failed.get_or_insert(err);
+        if !growth.add_reserve(buffer, event, min_size) {
+            std::process::abort();
+        }
+    }
+}
+
+pub struct CommandBuffer {
+    buffer: super::CommandBuffer,
+    device: *mut super::Device,
+    control: Option<AlignedStorage>,
+    command: (usize, usize),
+    pool: *mut MemoryPool,
+    growth: Option<Box<CommandMemoryGrowth>>
+}
+
+impl_init_err!(CommandBuffer);
+
+impl CommandBuffer {
+    pub fn new() -> CommandBufferBuilder {
+        CommandBufferBuilder::new()
+    }
+
+    pub fn create(builder: CommandBufferBuilder) -> Result<Self, BuildError> {
+        builder.validate()?;
+
+        let CommandBufferBuilder { device, control, allocator, pool, command, growth_pool, growth, chunk_sizes, .. } = builder;
+        let mut growth = if growth_pool.is_null() {
+            None
+        } else {
+            Some(Box::new(CommandMemoryGrowth::new(device, growth_pool, growth, chunk_sizes, allocator)?))
+        };
+
         let mut buffer = super::CommandBuffer::new();
         if buffer.initialize(device) {
+            if let Some(growth) = growth.as_mut() {
+                buffer.set_memory_callback(Some(grow_command_memory));
+                buffer.set_memory_callback_data(growth.as_mut() as *mut CommandMemoryGrowth as *mut c_void);
+            }
+
             let mut ret = Self {
                 buffer,
+                device,
                 control,
-                shared,
                 command,
-                pool
+                pool,
+                growth
             };
 
             ret.reset();
 
             Ok(ret)
         } else {
-            Err(Self::get_err())
+            Err(Self::get_err().into())
         }
     }
 
+    /// Hands the command buffer its initial command and control memory again. Any memory it grew
+    /// into is reclaimed, so previously recorded commands must have finished executing.
     pub fn reset(&mut self) {
+        if let Some(growth) = self.growth.as_mut() {
+            growth.reset();
+        }
+
+        // without initial command memory the buffer relies entirely on growing into more
         let command_pool = self.pool;
-        let command_start = self.command.0 as u64;
-        let command_size = self.command.1;
-        self.add_command_memory(command_pool, command_start, command_size);
-        let control_start = self.control.0;
-        let control_size = self.control.1;
-        self.add_control_memory(control_start, control_size);
+        if !command_pool.is_null() {
+            let command_start = self.command.0 as u64;
+            let command_size = self.command.1;
+            self.buffer.add_command_memory(command_pool, command_start, command_size);
+        }
+
+        if let Some((control_start, control_size)) = self.control.as_ref().map(|control| (control.as_ptr(), control.size())) {
+            self.buffer.add_control_memory(control_start, control_size);
+        }
+    }
+
+    /// Starts recording. The buffer stays borrowed until the returned `Recording` is finished and
+    /// the resulting `CommandList` is dropped, so it can neither be recorded into again nor reset
+    /// while its commands may still be submitted.
+    pub fn begin(&mut self) -> Recording<'_> {
+        if let Some(growth) = self.growth.as_mut() {
+            growth.failed = None;
+        }
+
+        self.buffer.begin_recording();
+        Recording {
+            buffer: self
+        }
+    }
+
+    /// Binds `address` as the uniform buffer at `index` for `stage` and writes `data` to it
+    /// through the command stream, so the new contents are only visible to subsequent commands.
+    pub fn update_uniform<T: Pod>(&mut self, stage: ShaderStage, index: i32, address: BufferAddress, data: &T) -> Result<(), BuildError> {
+        let (alignment, update_alignment) = unsafe {
+            (
+                ((*self.device).get_info(DeviceInfo::UniformBufferAlignment) as u64).max(1),
+                ((*self.device).get_info(DeviceInfo::UniformBufferUpdateAlignment) as usize).max(1)
+            )
+        };
+        let size = std::mem::size_of::<T>();
+
+        if !address.0.is_multiple_of(alignment) {
+            return Err(BuildError::Misaligned {
+                param: "address",
+                address: address.0 as usize,
+                align: alignment as usize
+            });
+        }
+
+        if size == 0 || !size.is_multiple_of(update_alignment) {
+            return Err(BuildError::InvalidSize {
+                param: "data",
+                size,
+                granularity: update_alignment
+            });
+        }
+
+        let bytes = bytemuck::bytes_of(data);
+        self.buffer.bind_uniform_buffer(stage, index, address, size);
+        self.buffer.update_uniform_buffer(address, size, 0, size, bytes.as_ptr());
+        Ok(())
+    }
+
+    pub fn copy_to_texture(&mut self, src: BufferAddress, dst: &Texture, region: &CopyRegion, flags: CopyFlags) -> Result<(), CopyError> {
+        if !Self::fits_texture(dst, region) {
+            return Err(CopyError::DestinationOutOfBounds(*region));
+        }
+
+        self.buffer.copy_buffer_to_texture(src, dst, 0 as _, region, flags);
+        Ok(())
+    }
+
+    pub fn copy_from_texture(&mut self, src: &Texture, region: &CopyRegion, dst: BufferAddress, flags: CopyFlags) -> Result<(), CopyError> {
+        if !Self::fits_texture(src, region) {
+            return Err(CopyError::SourceOutOfBounds(*region));
+        }
+
+        self.buffer.copy_texture_to_buffer(src, 0 as _, region, dst, flags);
+        Ok(())
+    }
+
+    /// Copies between two textures. Regions of different sizes are only accepted when `flags`
+    /// selects the 2D engine, which is the only one able to scale.
+    pub fn copy_texture(&mut self, src: &Texture, src_region: &CopyRegion, dst: &Texture, dst_region: &CopyRegion, flags: CopyFlags) -> Result<(), CopyError> {
+        if !Self::fits_texture(src, src_region) {
+            return Err(CopyError::SourceOutOfBounds(*src_region));
+        }
+
+        if !Self::fits_texture(dst, dst_region) {
+            return Err(CopyError::DestinationOutOfBounds(*dst_region));
+        }
+
+        let same_size = src_region.width == dst_region.width
+            && src_region.height == dst_region.height
+            && src_region.depth == dst_region.depth;
+
+        if !same_size && !flags.engine_2d() {
+            return Err(CopyError::SizeMismatch(*src_region, *dst_region));
+        }
+
+        self.buffer.copy_texture_to_texture(src, 0 as _, src_region, dst, 0 as _, dst_region, flags);
+        Ok(())
+    }
+
+    fn fits_texture(texture: &Texture, region: &CopyRegion) -> bool {
+        region.fits(texture.get_width(), texture.get_height(), texture.get_depth())
     }
 
     pub fn as_ref(&self) -> &super::CommandBuffer {
         &self.buffer
     }
 
-    pub fn as_mut(&mut self) -> &mut super::CommandBuffer {
+    /// The raw command buffer, for commands without a managed counterpart.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not begin or end recording, add command or control memory, change the
+    /// memory callback or finalize the buffer through it, as `begin`, `reset` and `Recording`
+    /// track that state.
+    pub unsafe fn raw_mut(&mut self) -> &mut super::CommandBuffer {
         &mut self.buffer
     }
 }
@@ -392,23 +1493,1321 @@ impl Deref for CommandBuffer {
     }
 }
 
-impl DerefMut for CommandBuffer {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        self.as_mut()
+impl Drop for CommandBuffer {
+    fn drop(&mut self) {
+        self.buffer.finalize();
     }
 }
 
-impl Drop for CommandBuffer {
-    fn drop(&mut self) {
-        if !self.shared {
-            unsafe {
-                libc::free(self.control.0 as *mut libc::c_void);
-            }
+pub struct Recording<'a> {
+    buffer: &'a mut CommandBuffer
+}
+
+impl<'a> Recording<'a> {
+    /// Ends recording. Fails if the buffer needed to grow while recording and couldn't, in which
+    /// case the recorded commands are incomplete and discarded.
+    pub fn finish(self) -> Result<CommandList<'a>, CommandMemoryError> {
+        let buffer = unsafe {
+            std::ptr::read(&self.buffer)
+        };
+        std::mem::forget(self);
+
+        let handle = buffer.buffer.end_recording();
+        match buffer.growth.as_ref().and_then(|growth| growth.failed) {
+            Some(err) => Err(err),
+            None => Ok(CommandList {
+                handle,
+                _buffer: PhantomData
+            })
+        }
+    }
+
+    pub fn update_uniform<T: Pod>(&mut self, stage: ShaderStage, index: i32, address: BufferAddress, data: &T) -> Result<(), BuildError> {
+        self.buffer.update_uniform(stage, index, address, data)
+    }
+
+    pub fn copy_to_texture(&mut self, src: BufferAddress, dst: &Texture, region: &CopyRegion, flags: CopyFlags) -> Result<(), CopyError> {
+        self.buffer.copy_to_texture(src, dst, region, flags)
+    }
+
+    pub fn copy_from_texture(&mut self, src: &Texture, region: &CopyRegion, dst: BufferAddress, flags: CopyFlags) -> Result<(), CopyError> {
+        self.buffer.copy_from_texture(src, region, dst, flags)
+    }
+
+    pub fn copy_texture(&mut self, src: &Texture, src_region: &CopyRegion, dst: &Texture, dst_region: &CopyRegion, flags: CopyFlags) -> Result<(), CopyError> {
+        self.buffer.copy_texture(src, src_region, dst, dst_region, flags)
+    }
+
+    /// The raw command buffer being recorded into. Every command has a safe counterpart on
+    /// `Recording`, this is only needed to manage the buffer itself.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not begin or end recording, add command or control memory, change the
+    /// memory callback or its data, or initialize or finalize the buffer through it, `finish` ends
+    /// the recording.
+    pub unsafe fn raw_mut(&mut self) -> &mut super::CommandBuffer {
+        self.commands()
+    }
+
+    fn commands(&mut self) -> &mut super::CommandBuffer {
+        &mut self.buffer.buffer
+    }
+}
+
+macro_rules! forward_commands {
+    ($($name:ident($($arg:ident: $ty:ty),*);)*) => {
+        impl<'a> Recording<'a> {
+            $(
+                #[allow(clippy::too_many_arguments)]
+                pub fn $name(&mut self, $($arg: $ty),*) {
+                    self.commands().$name($($arg),*)
+                }
+            )*
+        }
+    }
+}
+
+// commands that only record into the buffer, which can't break the recording
+forward_commands! {
+    set_render_targets(count: i32, target: *const *const Texture, texture_view: *const u8, depth_texture: *const u8, depth_view: *const u8);
+    set_scissor(x: i32, y: u32, w: u32, h: u32);
+    set_viewport(x: i32, y: u32, w: u32, h: u32);
+    clear_color(index: i32, color: *const f32, mask: ClearColorMask);
+    clear_texture(texture: *const Texture, texture_view: *const TextureView, copy_region: *const CopyRegion, color: *const f32, mask: ClearColorMask);
+    bind_program(program: *const super::Program, stages: ShaderStageBits);
+    bind_blend_state(state: *const BlendState);
+    bind_channel_mask_state(state: *const ChannelMaskState);
+    bind_color_state(state: *const ColorState);
+    bind_depth_stencil_state(state: *const DepthStencilState);
+    bind_multisample_state(state: *const MultisampleState);
+    bind_polygon_state(state: *const PolygonState);
+    set_blend_color(color: *const f32);
+    set_stencil_value_mask(faces: Face, mask: i32);
+    set_stencil_mask(faces: Face, mask: i32);
+    set_stencil_ref(faces: Face, reference: i32);
+    bind_vertex_attrib_state(count: i32, attribs: *const VertexAttribState);
+    bind_vertex_stream_state(count: i32, streams: *const VertexStreamState);
+    bind_vertex_buffer(index: i32, address: BufferAddress, size: usize);
+    bind_vertex_buffers(first: i32, count: i32, buffers: *const BufferRange);
+    draw_arrays(mode: DrawPrimitive, first: i32, count: i32);
+    draw_elements(mode: DrawPrimitive, index_type: IndexType, count: i32, index_buffer: BufferAddress);
+    draw_elements_base_vertex(mode: DrawPrimitive, index_type: IndexType, count: i32, index_buffer: BufferAddress, base_vertex: i32);
+    draw_arrays_instanced(mode: DrawPrimitive, first: i32, count: i32, base_instance: i32, instance_count: i32);
+    draw_elements_instanced(mode: DrawPrimitive, index_type: IndexType, count: i32, index_buffer: BufferAddress, base_vertex: i32, base_instance: i32, instance_count: i32);
+    draw_arrays_indirect(mode: DrawPrimitive, indirect_buffer: BufferAddress);
+    draw_elements_indirect(mode: DrawPrimitive, index_type: IndexType, index_buffer: BufferAddress, indirect_buffer: BufferAddress);
+    multi_draw_arrays_indirect_count(mode: DrawPrimitive, indirect_buffer: BufferAddress, parameter_buffer: BufferAddress, max_draw_count: i32, stride: isize);
+    multi_draw_elements_indirect_count(mode: DrawPrimitive, index_type: IndexType, index_buffer: BufferAddress, indirect_buffer: BufferAddress, parameter_buffer: BufferAddress, max_draw_count: i32, stride: isize);
+    dispatch_compute(groups_x: i32, groups_y: i32, groups_z: i32);
+    dispatch_compute_indirect(indirect_buffer: BufferAddress);
+    set_texture_pool(pool: *const TexturePool);
+    bind_storage_buffer(stage: ShaderStage, index: i32, address: BufferAddress, size: usize);
+    bind_storage_buffers(stage: ShaderStage, first: i32, count: i32, buffers: *const BufferRange);
+    bind_image(stage: ShaderStage, index: i32, image: ImageHandle);
+    bind_images(stage: ShaderStage, first: i32, count: i32, images: *const ImageHandle);
+    bind_uniform_buffer(stage: ShaderStage, index: i32, address: BufferAddress, size: usize);
+    bind_uniform_buffers(stage: ShaderStage, first: i32, count: i32, buffers: *const BufferRange);
+    update_uniform_buffer(address: BufferAddress, buffer_size: usize, update_offset: isize, update_size: usize, data: *const u8);
+    copy_buffer_to_buffer(src: BufferAddress, dst: BufferAddress, size: usize, flags: CopyFlags);
+    copy_buffer_to_texture(src: BufferAddress, dst: *const Texture, dst_view: *const TextureView, dst_region: *const CopyRegion, flags: CopyFlags);
+    copy_texture_to_buffer(src: *const Texture, src_view: *const TextureView, src_region: *const CopyRegion, dst: BufferAddress, flags: CopyFlags);
+    copy_texture_to_texture(src: *const Texture, src_view: *const TextureView, src_region: *const CopyRegion, dst: *const Texture, dst_view: *const TextureView, dst_region: *const CopyRegion, flags: CopyFlags);
+    fence_sync(sync: *mut SyncObject, condition: SyncCondition, flags: SyncFlags);
+    wait_sync(sync: *const SyncObject);
+    report_counter(counter: CounterType, buffer: BufferAddress);
+    reset_counter(counter: CounterType);
+    barrier(barriers: BarrierBits);
+    set_render_enable(enable: bool);
+    set_render_enable_conditional(mode: ConditionalRenderMode, counters: BufferAddress);
+    signal_event(event: *const super::Event, mode: EventSignalMode, location: EventSignalLocation, flags: EventSignalFlags, value: u32);
+    wait_event(event: *const super::Event, mode: EventWaitMode, value: u32);
+}
+
+impl<'a> Deref for Recording<'a> {
+    type Target = super::CommandBuffer;
+
+    fn deref(&self) -> &Self::Target {
+        self.buffer.as_ref()
+    }
+}
+
+impl<'a> Drop for Recording<'a> {
+    fn drop(&mut self) {
+        // the recording was never finished, its commands are discarded
+        self.buffer.buffer.end_recording();
+    }
+}
+
+/// Commands recorded into a `CommandBuffer`, valid for as long as the buffer's command memory
+/// is left untouched.
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone)]
+pub struct CommandList<'a> {
+    handle: CommandHandle,
+    _buffer: PhantomData<&'a CommandBuffer>
+}
+
+impl<'a> CommandList<'a> {
+    pub(crate) fn handle(&self) -> CommandHandle {
+        self.handle
+    }
+}
+
+pub struct QueueBuilder {
+    builder: super::QueueBuilder,
+    device: *const super::Device,
+    allocator: Arc<dyn HostAllocator>
+}
+
+impl QueueBuilder {
+    pub fn new() -> Self {
+        Self {
+            builder: {
+                let mut ret = super::QueueBuilder::new();
+                ret.set_defaults();
+                ret
+            },
+            device: 0 as _,
+            allocator: default_allocator()
+        }
+    }
+
+    /// Allocator the queue memory is taken from.
+    pub fn with_allocator(mut self, allocator: Arc<dyn HostAllocator>) -> Self {
+        self.allocator = allocator;
+        self
+    }
+
+    pub fn with_device(mut self, device: *const super::Device) -> Self {
+        self.set_device(device);
+        self.device = device;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: u32) -> Self {
+        self.set_flags(flags);
+        self
+    }
+
+    pub fn with_command_mem_size(mut self, size: usize) -> Self {
+        self.set_command_mem_size(size);
+        self
+    }
+
+    pub fn with_compute_mem_size(mut self, size: usize) -> Self {
+        self.set_compute_mem_size(size);
+        self
+    }
+
+    pub fn with_cmd_flush_threshold(mut self, size: usize) -> Self {
+        self.set_cmd_flush_threshold(size);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), BuildError> {
+        if self.device.is_null() {
+            Err(BuildError::MissingDevice)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn finish(self) -> Result<Queue, BuildError> {
+        Queue::create(self)
+    }
+}
+
+impl Deref for QueueBuilder {
+    type Target = super::QueueBuilder;
+
+    fn deref(&self) -> &Self::Target {
+        &self.builder
+    }
+}
+
+impl DerefMut for QueueBuilder {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.builder
+    }
+}
+
+pub struct Queue {
+    queue: super::Queue,
+    memory: AlignedStorage
+}
+
+impl_init_err!(Queue);
+
+impl Queue {
+    pub fn new() -> QueueBuilder {
+        QueueBuilder::new()
+    }
+
+    /// Allocates the queue memory the builder's settings require and initializes the queue with it.
+    pub fn create(mut builder: QueueBuilder) -> Result<Self, BuildError> {
+        builder.validate()?;
+
+        let size = align_up(builder.get_mem_size(), MEMORY_POOL_GRANULARITY);
+        let memory = AlignedStorage::alloc(builder.allocator.clone(), size, MEMORY_POOL_GRANULARITY)
+            .ok_or(BuildError::AllocationFailed { param: "memory", size })?;
+
+        builder.set_memory(memory.as_ptr(), size);
+
+        let mut queue = super::Queue::new();
+        if queue.init(builder.deref()) {
+            Ok(Self {
+                queue,
+                memory
+            })
+        } else {
+            Err(Self::get_err().into())
+        }
+    }
+
+    pub fn submit(&mut self, lists: &[CommandList]) {
+        self.submit_commands(lists.len() as i32, lists.as_ptr() as *const CommandHandle);
+    }
+
+    pub fn flush(&mut self) {
+        self.queue.flush();
+    }
+
+    /// Blocks until all work submitted to the queue has completed.
+    pub fn finish(&mut self) {
+        self.queue.finish();
+    }
+
+    pub fn present(&mut self, window: &mut Window, texture_index: i32) {
+        self.present_texture(window, texture_index);
+    }
+
+    pub fn error(&mut self) -> Result<(), QueueError> {
+        let mut info = QueueErrorInfo::default();
+        match self.get_error(&mut info) {
+            QueueGetErrorResult::GpuNoError => Ok(()),
+            QueueGetErrorResult::GpuErrorUnknown => Err(QueueError::Unknown),
+            QueueGetErrorResult::GpuErrorMmuFault => {
+                let fault = unsafe { info.mmu_fault };
+                Err(QueueError::MmuFault {
+                    address: fault.fault_address,
+                    access: fault.access_type
+                })
+            },
+            QueueGetErrorResult::GpuErrorPbdmaException => Err(QueueError::PbdmaException),
+            QueueGetErrorResult::GpuErrorEngineException => Err(QueueError::EngineException),
+            QueueGetErrorResult::GpuErrorTimeout => Err(QueueError::Timeout)
+        }
+    }
+
+    pub fn as_ref(&self) -> &super::Queue {
+        &self.queue
+    }
+
+    pub fn as_mut(&mut self) -> &mut super::Queue {
+        &mut self.queue
+    }
+}
+
+impl Deref for Queue {
+    type Target = super::Queue;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+impl DerefMut for Queue {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut()
+    }
+}
+
+impl Drop for Queue {
+    fn drop(&mut self) {
+        self.queue.finish();
+        self.fini();
+    }
+}
+
+pub struct ProgramBuilder<'a> {
+    device: *mut super::Device,
+    shaders: Vec<(&'a [u8], &'a [u8])>,
+    name: Option<&'a CStr>
+}
+
+impl<'a> ProgramBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            device: 0 as _,
+            shaders: Vec::new(),
+            name: None
+        }
+    }
+
+    pub fn with_device(mut self, device: *mut super::Device) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// Adds a precompiled shader stage. `code` is the GPU code section, which gets copied into
+    /// the program's shader memory, and `control` is the control section handed to the driver as-is.
+    pub fn with_shader(mut self, code: &'a [u8], control: &'a [u8]) -> Self {
+        self.shaders.push((code, control));
+        self
+    }
+
+    pub fn with_name(mut self, name: &'a CStr) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), BuildError> {
+        if self.device.is_null() {
+            Err(BuildError::MissingDevice)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn finish(self) -> Result<Program, BuildError> {
+        Program::create(self)
+    }
+}
+
+pub struct Program {
+    program: super::Program,
+    pool: MemPool
+}
+
+impl_init_err!(Program);
+
+impl Program {
+    pub fn new<'a>() -> ProgramBuilder<'a> {
+        ProgramBuilder::new()
+    }
+
+    pub fn create(builder: ProgramBuilder) -> Result<Self, BuildError> {
+        builder.validate()?;
+
+        let ProgramBuilder { device, shaders, name } = builder;
+
+        let (alignment, padding) = unsafe {
+            (
+                (*device).get_info(DeviceInfo::ShaderCodeAlignment) as usize,
+                (*device).get_info(DeviceInfo::ShaderCodeMemoryPoolPaddingSize) as usize
+            )
+        };
+
+        // the GPU prefetches past the end of the last shader, so the pool needs trailing padding
+        let code_size = shaders.iter().fold(0, |size, (code, _)| align_up(size, alignment) + code.len());
+        let pool_size = align_up(code_size + padding, MEMORY_POOL_GRANULARITY);
+
+        let pool = MemPool::new()
+            .with_device(device)
+            .with_flags(
+                MemoryPoolFlags::new()
+                    .with_cpu_uncached(true)
+                    .with_gpu_cached(true)
+                    .with_shader_code(true)
+            )
+            .make_storage(pool_size, None)
+            .finish()?;
+
+        let memory = pool.map();
+        let address = pool.get_buffer_address();
+        let mut offset = 0;
+        let mut stage_data = Vec::with_capacity(shaders.len());
+        for (code, control) in shaders.iter() {
+            offset = align_up(offset, alignment);
+            unsafe {
+                std::ptr::copy_nonoverlapping(code.as_ptr(), memory.add(offset), code.len());
+            }
+            stage_data.push(ShaderData {
+                data: address.offset(offset as u64),
+                control: control.as_ptr()
+            });
+            offset += code.len();
+        }
+        pool.flush(0, pool_size);
+
+        let mut program = super::Program::new();
+        if !program.initialize(device) {
+            return Err(Self::get_err().into());
+        }
+
+        if let Some(name) = name {
+            program.set_name(name.as_ptr());
+        }
+
+        if program.set_shaders(stage_data.len() as i32, stage_data.as_ptr()) {
+            Ok(Self {
+                program,
+                pool
+            })
+        } else {
+            program.finalize();
+            Err(Self::get_err().into())
+        }
+    }
+
+    pub fn pool(&self) -> &MemPool {
+        &self.pool
+    }
+
+    pub fn as_ref(&self) -> &super::Program {
+        &self.program
+    }
+
+    pub fn as_mut(&mut self) -> &mut super::Program {
+        &mut self.program
+    }
+}
+
+impl Deref for Program {
+    type Target = super::Program;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+impl DerefMut for Program {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut()
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        self.finalize();
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VertexAttribute {
+    pub format: Format,
+    pub stream: usize,
+    pub offset: usize
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VertexInputState {
+    attribs: Vec<VertexAttribState>,
+    streams: Vec<VertexStreamState>
+}
+
+impl VertexInputState {
+    pub const MAX_ATTRIBUTES: usize = 16;
+    pub const MAX_STREAMS: usize = 16;
+
+    /// Builds the attribute and stream state for `attributes`, where attribute `n` in the slice
+    /// is bound to shader location `n`. Every referenced stream gets a tightly packed stride, which
+    /// can be overridden with `with_stride`.
+    pub fn new(attributes: &[VertexAttribute]) -> Result<Self, BuildError> {
+        if attributes.len() > Self::MAX_ATTRIBUTES {
+            return Err(BuildError::IndexOutOfRange {
+                param: "attributes",
+                index: attributes.len() - 1,
+                max: Self::MAX_ATTRIBUTES
+            });
+        }
+
+        let mut strides = Vec::new();
+        let mut attribs = Vec::with_capacity(attributes.len());
+        for attrib in attributes {
+            Self::check_stream(attrib.stream)?;
+            let size = attrib.format.size().ok_or(BuildError::UnsizedFormat(attrib.format))?;
+
+            if strides.len() <= attrib.stream {
+                strides.resize(attrib.stream + 1, 0);
+            }
+            strides[attrib.stream] = strides[attrib.stream].max(attrib.offset + size);
+
+            let mut state = VertexAttribState::new();
+            state.set_defaults();
+            state.set_format(attrib.format, attrib.offset as isize);
+            state.set_stream_index(attrib.stream as i32);
+            attribs.push(state);
+        }
+
+        let streams = strides.iter().map(|stride| {
+            let mut state = VertexStreamState::new();
+            state.set_defaults();
+            state.set_stride(*stride as isize);
+            state
+        }).collect();
+
+        Ok(Self {
+            attribs,
+            streams
+        })
+    }
+
+    pub fn with_stride(mut self, stream: usize, stride: usize) -> Result<Self, BuildError> {
+        self.stream_mut(stream)?.set_stride(stride as isize);
+        Ok(self)
+    }
+
+    pub fn with_divisor(mut self, stream: usize, divisor: i32) -> Result<Self, BuildError> {
+        self.stream_mut(stream)?.set_divisor(divisor);
+        Ok(self)
+    }
+
+    fn check_stream(stream: usize) -> Result<(), BuildError> {
+        if stream < Self::MAX_STREAMS {
+            Ok(())
+        } else {
+            Err(BuildError::IndexOutOfRange {
+                param: "stream",
+                index: stream,
+                max: Self::MAX_STREAMS
+            })
+        }
+    }
+
+    /// Streams no attribute reads from are added with default state on first use.
+    fn stream_mut(&mut self, stream: usize) -> Result<&mut VertexStreamState, BuildError> {
+        Self::check_stream(stream)?;
+        while self.streams.len() <= stream {
+            let mut state = VertexStreamState::new();
+            state.set_defaults();
+            self.streams.push(state);
+        }
+        Ok(&mut self.streams[stream])
+    }
+
+    pub fn attribs(&self) -> &[VertexAttribState] {
+        &self.attribs
+    }
+
+    pub fn streams(&self) -> &[VertexStreamState] {
+        &self.streams
+    }
+
+    pub fn bind(&self, recording: &mut Recording) {
+        recording.bind_vertex_attrib_state(self.attribs.len() as i32, self.attribs.as_ptr());
+        recording.bind_vertex_stream_state(self.streams.len() as i32, self.streams.as_ptr());
+    }
+}
+
+pub struct Fence {
+    sync: super::SyncObject
+}
+
+impl_init_err!(Fence);
+
+impl Fence {
+    pub fn create(device: *mut super::Device) -> Result<Self, InitializeError> {
+        let mut sync = super::SyncObject::new();
+        if sync.initialize(device) {
+            Ok(Self {
+                sync
+            })
+        } else {
+            Err(Self::get_err())
+        }
+    }
+
+    pub fn wait(&self, timeout: Duration) -> SyncWaitResult {
+        self.sync.wait(timeout.as_nanos().min(u64::MAX as u128) as u64)
+    }
+
+    pub fn is_signaled(&self) -> bool {
+        matches!(self.sync.wait(0), SyncWaitResult::AlreadySignaled | SyncWaitResult::ConditionSatisfied)
+    }
+
+    pub fn as_ref(&self) -> &super::SyncObject {
+        &self.sync
+    }
+
+    pub fn as_mut(&mut self) -> &mut super::SyncObject {
+        &mut self.sync
+    }
+}
+
+impl Deref for Fence {
+    type Target = super::SyncObject;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+impl DerefMut for Fence {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut()
+    }
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        self.finalize();
+    }
+}
+
+/// Values waiting on fences placed on a queue, handed back oldest first once their fence has
+/// signaled. Fences nobody else holds on to anymore are reused by later calls to `push`.
+struct FencedFrames<T> {
+    device: *mut super::Device,
+    in_flight: VecDeque<(Arc<Fence>, T)>,
+    spare_fences: Vec<Fence>
+}
+
+impl<T> FencedFrames<T> {
+    fn new(device: *mut super::Device) -> Self {
+        Self {
+            device,
+            in_flight: VecDeque::new(),
+            spare_fences: Vec::new()
+        }
+    }
+
+    /// A spare fence, or a new one if none is left. Taken before `push` so a failure leaves the
+    /// caller's state untouched.
+    fn next_fence(&mut self) -> Result<Fence, InitializeError> {
+        match self.spare_fences.pop() {
+            Some(fence) => Ok(fence),
+            None => Fence::create(self.device)
+        }
+    }
+
+    /// Places `fence` on `queue` and flushes it, `value` is returned by `pop_signaled` once the
+    /// fence signals.
+    fn push(&mut self, queue: &mut super::Queue, mut fence: Fence, value: T) -> Arc<Fence> {
+        queue.fence_sync(fence.as_mut(), SyncCondition::AllGpuCommandsComplete, SyncFlags::new());
+        queue.flush();
+
+        let fence = Arc::new(fence);
+        self.in_flight.push_back((fence.clone(), value));
+        fence
+    }
+
+    fn pop_signaled(&mut self) -> Option<T> {
+        if !self.in_flight.front()?.0.is_signaled() {
+            return None;
+        }
+
+        let (fence, value) = self.in_flight.pop_front()?;
+        if let Ok(fence) = Arc::try_unwrap(fence) {
+            self.spare_fences.push(fence);
+        }
+        Some(value)
+    }
+
+    /// Blocks until every fence has signaled or `timeout` has passed.
+    fn wait(&self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        for (fence, _) in self.in_flight.iter() {
+            fence.wait(deadline.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    fn pending(&self) -> impl Iterator<Item = &T> {
+        self.in_flight.iter().map(|(_, value)| value)
+    }
+
+    /// Removes every value, whether its fence has signaled or not.
+    fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.in_flight.drain(..).map(|(_, value)| value)
+    }
+}
+
+/// CPU visible memory that `GpuTimer`s and `OcclusionQuery`s take their counter reports from,
+/// so they don't each need a pool of their own.
+pub struct ReportPool {
+    device: *mut super::Device,
+    reports: SubAllocator
+}
+
+impl ReportPool {
+    const REPORT_SIZE: usize = std::mem::size_of::<ReportValue>();
+
+    /// Makes room for `capacity` reports. Timers and queries use two each.
+    pub fn create(device: *mut super::Device, capacity: usize) -> Result<Self, BuildError> {
+        let pool = MemPool::new()
+            .with_device(device)
+            .with_flags(
+                MemoryPoolFlags::new()
+                    .with_cpu_uncached(true)
+                    .with_gpu_cached(true)
+            )
+            .make_storage(align_up(capacity.max(1) * Self::REPORT_SIZE, MEMORY_POOL_GRANULARITY), None)
+            .finish()?;
+
+        Ok(Self {
+            device,
+            reports: SubAllocator::new(pool)
+        })
+    }
+
+    fn alloc(&self, count: usize) -> Result<Allocation<'_>, MemPoolError> {
+        self.reports.alloc(count * Self::REPORT_SIZE, Self::REPORT_SIZE)
+    }
+}
+
+/// Pointer to the first report of `reports` in the CPU mapping of its pool.
+fn report_values(reports: &Allocation) -> *const ReportValue {
+    unsafe {
+        reports.pool().map().add(reports.range().start) as *const ReportValue
+    }
+}
+
+/// Measures GPU time between two points of a command buffer using timestamp counters.
+pub struct GpuTimer<'a> {
+    pool: &'a ReportPool,
+    reports: Allocation<'a>
+}
+
+impl<'a> GpuTimer<'a> {
+    pub fn create(pool: &'a ReportPool) -> Result<Self, MemPoolError> {
+        Ok(Self {
+            pool,
+            reports: pool.alloc(2)?
+        })
+    }
+
+    pub fn begin(&self, recording: &mut Recording) {
+        recording.report_counter(CounterType::Timestamp, self.reports.address());
+    }
+
+    pub fn end(&self, recording: &mut Recording) {
+        let offset = std::mem::size_of::<ReportValue>() as u64;
+        recording.report_counter(CounterType::Timestamp, self.reports.address().offset(offset));
+    }
+
+    /// Time elapsed between `begin` and `end`, or `None` if `fence` has not signaled yet.
+    /// `fence` has to be fenced after the commands recorded by `end` were submitted.
+    pub fn elapsed(&self, fence: &Fence) -> Option<Duration> {
+        if !fence.is_signaled() {
+            return None;
+        }
+
+        let (begin, end) = unsafe {
+            let values = report_values(&self.reports);
+            (
+                (*self.pool.device).get_timestamp_nanos(values),
+                (*self.pool.device).get_timestamp_nanos(values.add(1))
+            )
+        };
+
+        Some(Duration::from_nanos(end.saturating_sub(begin)))
+    }
+}
+
+// `OcclusionQuery` relies on a report being exactly a 64-bit counter followed by a 64-bit
+// timestamp, see there
+const _: () = assert!(std::mem::size_of::<ReportValue>() == 2 * std::mem::size_of::<u64>());
+
+/// Counts the samples passing depth and stencil tests between `begin` and `end`.
+///
+/// The query holds two reports back to back, each a 64-bit counter followed by a 64-bit
+/// timestamp:
+///
+/// | offset | before `end` | after `end` |
+/// |--------|--------------|-------------|
+/// | 0x00   | begin count  | begin count |
+/// | 0x08   | timestamp    | end count   |
+/// | 0x10   | end count    | end count   |
+/// | 0x18   | timestamp    | timestamp   |
+///
+/// `set_render_enable_conditional` compares the two 64-bit values at the address it is given,
+/// which is why `end` copies the end count next to the begin count.
+pub struct OcclusionQuery<'a> {
+    reports: Allocation<'a>
+}
+
+// both only borrow their `ReportPool`, which is what makes them `Send` and `Sync`
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<GpuTimer>();
+    assert_send_sync::<OcclusionQuery>();
+};
+
+impl<'a> OcclusionQuery<'a> {
+    const REPORT_SIZE: u64 = std::mem::size_of::<ReportValue>() as u64;
+    const COUNTER_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+    pub fn create(pool: &'a ReportPool) -> Result<Self, MemPoolError> {
+        Ok(Self {
+            reports: pool.alloc(2)?
+        })
+    }
+
+    pub fn begin(&self, recording: &mut Recording) {
+        recording.report_counter(CounterType::SamplesPassed, self.reports.address());
+    }
+
+    pub fn end(&self, recording: &mut Recording) {
+        let address = self.reports.address();
+        recording.report_counter(CounterType::SamplesPassed, address.offset(Self::REPORT_SIZE));
+        recording.barrier(BarrierBits::new().with_order_fragments(true));
+        recording.copy_buffer_to_buffer(
+            address.offset(Self::REPORT_SIZE),
+            address.offset(Self::COUNTER_SIZE),
+            Self::COUNTER_SIZE as usize,
+            CopyFlags::new()
+        );
+    }
+
+    /// Skips the following draws if no samples passed between `begin` and `end`, without waiting
+    /// on the CPU for the result. Rendering is re-enabled with `render_always`.
+    pub fn render_if_visible(&self, recording: &mut Recording) {
+        recording.barrier(BarrierBits::new().with_order_indirect_data(true));
+        recording.set_render_enable_conditional(ConditionalRenderMode::RenderIfNotEqual, self.reports.address());
+    }
+
+    /// Ends `render_if_visible`, draws recorded after this are no longer skipped.
+    pub fn render_always(&self, recording: &mut Recording) {
+        recording.set_render_enable(true);
+    }
+
+    /// Number of samples that passed, or `None` if `fence` has not signaled yet.
+    pub fn samples_passed(&self, fence: &Fence) -> Option<u64> {
+        if !fence.is_signaled() {
+            return None;
+        }
+
+        let (begin, end) = unsafe {
+            let values = report_values(&self.reports);
+            (std::ptr::read_volatile(&(*values).counter), std::ptr::read_volatile(&(*values.add(1)).counter))
+        };
+
+        Some(end.wrapping_sub(begin))
+    }
+
+    pub fn any_samples_passed(&self, fence: &Fence) -> Option<bool> {
+        self.samples_passed(fence).map(|samples| samples != 0)
+    }
+}
+
+pub struct EventBuilder<'a> {
+    builder: super::EventBuilder,
+    storage: Option<(&'a MemPool, usize)>
+}
+
+impl<'a> EventBuilder<'a> {
+    const VALUE_SIZE: usize = std::mem::size_of::<u32>();
+
+    pub fn new() -> Self {
+        Self {
+            builder: {
+                let mut ret = super::EventBuilder::new();
+                ret.set_defaults();
+                ret
+            },
+            storage: None
+        }
+    }
+
+    /// The event value lives at `offset` in `pool`, which needs to be CPU accessible for the
+    /// value to be polled from the CPU.
+    pub fn with_storage(mut self, pool: &'a MemPool, offset: usize) -> Self {
+        self.builder.set_storage(pool.as_ref(), offset as i64);
+        self.storage = Some((pool, offset));
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), BuildError> {
+        let (pool, offset) = self.storage.ok_or(BuildError::NullMemory("storage"))?;
+
+        if !offset.is_multiple_of(Self::VALUE_SIZE) {
+            return Err(BuildError::Misaligned {
+                param: "offset",
+                address: offset,
+                align: Self::VALUE_SIZE
+            });
+        }
+
+        let min = offset.saturating_add(Self::VALUE_SIZE);
+        if pool.get_size() < min {
+            return Err(BuildError::TooSmall {
+                param: "storage",
+                size: pool.get_size(),
+                min
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<Event<'a>, BuildError> {
+        Event::create(&self)
+    }
+}
+
+impl<'a> Deref for EventBuilder<'a> {
+    type Target = super::EventBuilder;
+
+    fn deref(&self) -> &Self::Target {
+        &self.builder
+    }
+}
+
+/// A 32-bit value in GPU memory that command buffers can signal and wait on mid-stream, and the
+/// CPU can poll, wait on or signal.
+pub struct Event<'a> {
+    event: super::Event,
+    _storage: PhantomData<&'a MemPool>
+}
+
+impl<'a> Event<'a> {
+    pub fn new() -> EventBuilder<'a> {
+        EventBuilder::new()
+    }
+
+    pub fn create(builder: &EventBuilder<'a>) -> Result<Self, BuildError> {
+        builder.validate()?;
+
+        let mut event = super::Event::new();
+        if event.initialize(builder.deref()) {
+            Ok(Self {
+                event,
+                _storage: PhantomData
+            })
+        } else {
+            Err(InitializeError("Event").into())
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.get_value()
+    }
+
+    /// Whether the current value satisfies `mode` against `value`, using the same comparison the
+    /// GPU uses for `wait_event`.
+    pub fn poll(&self, mode: EventWaitMode, value: u32) -> bool {
+        let current = self.get_value();
+        match mode {
+            EventWaitMode::Equal => current == value,
+            EventWaitMode::GequalWrap => current.wrapping_sub(value) as i32 >= 0
+        }
+    }
+
+    /// Spins until `poll` succeeds or `timeout` runs out, returning whether it succeeded.
+    pub fn wait(&self, mode: EventWaitMode, value: u32, timeout: Duration) -> bool {
+        let start = std::time::Instant::now();
+        loop {
+            if self.poll(mode, value) {
+                return true;
+            }
+
+            if start.elapsed() >= timeout {
+                return false;
+            }
+
+            std::thread::yield_now();
+        }
+    }
+
+    pub fn as_ref(&self) -> &super::Event {
+        &self.event
+    }
+
+    pub fn as_mut(&mut self) -> &mut super::Event {
+        &mut self.event
+    }
+}
+
+impl<'a> Deref for Event<'a> {
+    type Target = super::Event;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_ref()
+    }
+}
+
+impl<'a> DerefMut for Event<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut()
+    }
+}
+
+impl<'a> Drop for Event<'a> {
+    fn drop(&mut self) {
+        self.finalize();
+    }
+}
+
+/// Completion of the uploads sent off together by `Uploader::submit`.
+#[derive(Clone)]
+pub struct UploadToken {
+    fence: Arc<Fence>
+}
+
+impl UploadToken {
+    pub fn is_complete(&self) -> bool {
+        self.fence.is_signaled()
+    }
+
+    pub fn wait(&self, timeout: Duration) -> SyncWaitResult {
+        self.fence.wait(timeout)
+    }
+}
+
+/// Copies CPU data into buffers and textures, including ones the CPU can't access. Data is staged
+/// in a `RingAllocator` and the copies are recorded into a command buffer dedicated to uploads
+/// until `submit` sends them off.
+pub struct Uploader {
+    staging: RingAllocator,
+    commands: CommandBuffer,
+    pending: Vec<CommandHandle>,
+    // doesn't keep the fence from being recycled, a fence that is gone has signaled
+    last_submit: Weak<Fence>
+}
+
+impl Uploader {
+    const STAGING_ALIGNMENT: usize = 0x200;
+
+    pub fn new(staging: RingAllocator, commands: CommandBuffer) -> Self {
+        Self {
+            staging,
+            commands,
+            pending: Vec::new(),
+            last_submit: Weak::new()
+        }
+    }
+
+    pub fn upload_buffer(&mut self, dst: &Buffer, offset: usize, data: &[u8]) -> Result<(), UploadError> {
+        let size = dst.get_size();
+        if offset > size || data.len() > size - offset {
+            return Err(UploadError::BufferOutOfBounds(offset..offset.saturating_add(data.len()), size));
+        }
+
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let src = self.stage(data)?;
+        let mut recording = self.begin();
+        recording.copy_buffer_to_buffer(src, dst.get_address().offset(offset as u64), data.len(), CopyFlags::new());
+        let handle = recording.finish()?.handle();
+        self.pending.push(handle);
+        Ok(())
+    }
+
+    /// Copies `data`, tightly packed texels of the texture's format, into `region` of mip level
+    /// `level`.
+    pub fn upload_texture(&mut self, dst: &Texture, level: i32, region: &CopyRegion, data: &[u8]) -> Result<(), UploadError> {
+        if level < 0 || level >= dst.get_levels() {
+            return Err(UploadError::InvalidLevel(level));
+        }
+
+        let (width, height, depth) = Self::level_size(dst, level);
+        if !region.fits(width, height, depth) {
+            return Err(CopyError::DestinationOutOfBounds(*region).into());
+        }
+
+        if let Some(element_size) = dst.get_format().size() {
+            let expected = region.width as usize * region.height as usize * region.depth as usize * element_size;
+            if data.len() != expected {
+                return Err(UploadError::DataSizeMismatch {
+                    expected,
+                    actual: data.len()
+                });
+            }
+        }
+
+        let src = self.stage(data)?;
+        let mut view = TextureView::new();
+        view.set_defaults();
+        view.set_levels(level, 1);
+
+        let mut recording = self.begin();
+        recording.copy_buffer_to_texture(src, dst, &view, region, CopyFlags::new());
+        let handle = recording.finish()?.handle();
+        self.pending.push(handle);
+        Ok(())
+    }
+
+    /// Submits the uploads recorded since the previous call to `queue`. Their staging memory is
+    /// reused once the returned token completes.
+    pub fn submit(&mut self, queue: &mut super::Queue) -> Result<UploadToken, InitializeError> {
+        self.staging.flush();
+
+        if !self.pending.is_empty() {
+            queue.submit_commands(self.pending.len() as i32, self.pending.as_ptr());
+            self.pending.clear();
+        }
+
+        let fence = self.staging.end_frame(queue)?;
+        self.last_submit = Arc::downgrade(&fence);
+        Ok(UploadToken {
+            fence
+        })
+    }
+
+    fn stage(&mut self, data: &[u8]) -> Result<BufferAddress, MemPoolError> {
+        let mut allocation = self.staging.alloc(data.len(), Self::STAGING_ALIGNMENT)?;
+        allocation.as_mut_slice()[..data.len()].copy_from_slice(data);
+        Ok(allocation.address())
+    }
+
+    fn begin(&mut self) -> Recording<'_> {
+        // command memory can only be rewound once nothing recorded into it is pending
+        let idle = self.last_submit.upgrade().is_none_or(|fence| fence.is_signaled());
+        if self.pending.is_empty() && idle {
+            self.commands.reset();
+        }
+
+        self.commands.begin()
+    }
+
+    /// Width, height and depth of mip `level`. Layers of array textures aren't mipmapped.
+    fn level_size(texture: &Texture, level: i32) -> (i32, i32, i32) {
+        let shrink = |size: i32| (size >> level).max(1);
+        let target = texture.get_target();
+
+        let height = match target {
+            TextureTarget::Target1DArray => texture.get_height(),
+            _ => shrink(texture.get_height())
+        };
+
+        let depth = match target {
+            TextureTarget::Target3D => shrink(texture.get_depth()),
+            _ => texture.get_depth()
+        };
+
+        (shrink(texture.get_width()), height, depth)
+    }
+}
+
+/// Raw objects that have to be finalized, used to hand them to a `DeferredDeleter`.
+pub trait Finalize {
+    fn finalize(&mut self);
+}
+
+macro_rules! impl_finalize {
+    ($($ty:ty),*) => {
+        $(
+            impl Finalize for $ty {
+                fn finalize(&mut self) {
+                    <$ty>::finalize(self)
+                }
+            }
+        )*
+    }
+}
+
+impl_finalize!(Buffer, Texture, TexturePool, MemoryPool, super::Program);
+
+/// Finalizes the wrapped object when dropped.
+pub struct Finalized<T: Finalize>(pub T);
+
+impl<T: Finalize> Drop for Finalized<T> {
+    fn drop(&mut self) {
+        self.0.finalize();
+    }
+}
+
+/// Holds on to resources that submitted work may still use, like pools, buffers, textures and
+/// programs, and drops them once the fence of the frame they were retired in signals.
+///
+/// The driver can defer finalization as well: devices created with `deferred_finalize` set
+/// release objects once `apply_deferred_finalizes` is called with a large enough age.
+///
+/// Dropping the deleter only waits `DROP_TIMEOUT` for the frames in flight, and leaks whatever
+/// the GPU may still be using after that, including everything deferred since the last
+/// `end_frame`. Call `shutdown` to wait as long as needed and find out whether anything is left.
+pub struct DeferredDeleter {
+    retired: Vec<Box<dyn Send>>,
+    frames: FencedFrames<Vec<Box<dyn Send>>>
+}
+
+impl DeferredDeleter {
+    /// How long dropping the deleter waits for frames still in flight.
+    pub const DROP_TIMEOUT: Duration = Duration::from_secs(1);
+
+    pub fn new(device: *mut super::Device) -> Self {
+        Self {
+            retired: Vec::new(),
+            frames: FencedFrames::new(device)
+        }
+    }
+
+    /// Drops `resource` once the GPU is done with the current frame.
+    pub fn defer<T: Send + 'static>(&mut self, resource: T) {
+        self.retired.push(Box::new(resource));
+    }
+
+    /// Finalizes a raw object once the GPU is done with the current frame.
+    pub fn defer_finalize<T: Finalize + Send + 'static>(&mut self, object: T) {
+        self.defer(Finalized(object))
+    }
+
+    /// Closes the current frame by fencing `queue`, after everything that may use the resources
+    /// deferred so far has been submitted.
+    pub fn end_frame(&mut self, queue: &mut super::Queue) -> Result<(), InitializeError> {
+        if !self.retired.is_empty() {
+            let fence = self.frames.next_fence()?;
+            let retired = std::mem::take(&mut self.retired);
+            self.frames.push(queue, fence, retired);
+        }
+
+        self.collect();
+        Ok(())
+    }
+
+    /// Drops the resources of every frame whose fence has signaled.
+    pub fn collect(&mut self) {
+        while let Some(resources) = self.frames.pop_signaled() {
+            drop(resources);
+        }
+    }
+
+    /// Number of resources not dropped yet.
+    pub fn pending(&self) -> usize {
+        self.retired.len() + self.frames.pending().map(Vec::len).sum::<usize>()
+    }
+
+    /// Waits up to `timeout` for the frames in flight and drops their resources. Gives the deleter
+    /// back if some are still pending, either because their fence hasn't signaled or because they
+    /// were deferred after the last `end_frame` and have no fence to wait on.
+    pub fn shutdown(mut self, timeout: Duration) -> Result<(), Self> {
+        self.frames.wait(timeout);
+        self.collect();
+
+        if self.pending() == 0 {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Drop for DeferredDeleter {
+    /// Waits up to `DROP_TIMEOUT` for the frames in flight. Resources whose fence hasn't signaled
+    /// by then, and those deferred after the last `end_frame` that have no fence at all, may still
+    /// be in use by the GPU and are leaked rather than dropped.
+    fn drop(&mut self) {
+        self.frames.wait(Self::DROP_TIMEOUT);
+        self.collect();
+
+        for resources in self.frames.drain() {
+            std::mem::forget(resources);
         }
+        std::mem::forget(std::mem::take(&mut self.retired));
     }
 }
 
 unsafe impl Send for MemPool {}
 unsafe impl Sync for MemPool {}
 unsafe impl Send for CommandBuffer {}
-unsafe impl Sync for CommandBuffer {}
\ No newline at end of file
+unsafe impl Sync for CommandBuffer {}
+unsafe impl Send for Program {}
+unsafe impl Sync for Program {}
+unsafe impl Send for Fence {}
+unsafe impl Sync for Fence {}
+unsafe impl Send for ReportPool {}
+unsafe impl Sync for ReportPool {}
+unsafe impl<'a> Send for Event<'a> {}
+unsafe impl<'a> Sync for Event<'a> {}
+unsafe impl Send for Queue {}
+unsafe impl Sync for Queue {}
+unsafe impl Send for Device {}
+unsafe impl Sync for Device {}
+unsafe impl Send for RingAllocator {}
+unsafe impl Sync for RingAllocator {}
+unsafe impl Send for GpuHeap {}
+unsafe impl Sync for GpuHeap {}
+unsafe impl Send for DeferredDeleter {}
\ No newline at end of file
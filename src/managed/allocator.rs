@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct AllocatorStats {
+    pub size: usize,
+    pub used: usize,
+    pub allocations: usize,
+    pub free_blocks: usize,
+    pub largest_free_block: usize
+}
+
+impl AllocatorStats {
+    pub fn free(&self) -> usize {
+        self.size - self.used
+    }
+
+    /// Share of the free space that can't be handed out as a single allocation, from 0 (all free
+    /// space is contiguous) to 1.
+    pub fn fragmentation(&self) -> f32 {
+        if self.free() == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_free_block as f32 / self.free() as f32
+        }
+    }
+}
+
+/// Best-fit bookkeeping of the free space in `0..size`. It never touches the memory it manages,
+/// which makes it usable for any pool or address space.
+///
+/// Free blocks are indexed both by offset, to coalesce neighbours on free, and by size, to find
+/// the smallest block an allocation fits in.
+#[derive(Debug, Clone)]
+pub struct RangeAllocator {
+    size: usize,
+    used: usize,
+    allocations: usize,
+    by_offset: BTreeMap<usize, usize>,
+    by_size: BTreeSet<(usize, usize)>
+}
+
+impl RangeAllocator {
+    pub fn new(size: usize) -> Self {
+        let mut ret = Self {
+            size,
+            used: 0,
+            allocations: 0,
+            by_offset: BTreeMap::new(),
+            by_size: BTreeSet::new()
+        };
+
+        if size != 0 {
+            ret.insert_free(0, size);
+        }
+
+        ret
+    }
+
+    /// Finds `size` bytes starting at a multiple of `align`, which has to be a power of two.
+    pub fn alloc(&mut self, size: usize, align: usize) -> Option<Range<usize>> {
+        debug_assert!(align.is_power_of_two());
+        let size = size.max(1);
+
+        let (block_size, block_start, start) = self.by_size
+            .range((size, 0)..)
+            .find_map(|&(block_size, block_start)| {
+                let start = block_start.checked_add(align - 1)? & !(align - 1);
+                if start.checked_add(size)? <= block_start + block_size {
+                    Some((block_size, block_start, start))
+                } else {
+                    None
+                }
+            })?;
+
+        self.remove_free(block_start, block_size);
+
+        if start > block_start {
+            self.insert_free(block_start, start - block_start);
+        }
+
+        let end = start + size;
+        let block_end = block_start + block_size;
+        if end < block_end {
+            self.insert_free(end, block_end - end);
+        }
+
+        self.used += size;
+        self.allocations += 1;
+
+        Some(start..end)
+    }
+
+    /// Returns a range obtained from `alloc` to the free space.
+    pub fn free(&mut self, range: Range<usize>) {
+        debug_assert!(range.start < range.end && range.end <= self.size);
+        debug_assert!(self.by_offset.range(..range.end).next_back().is_none_or(|(&start, &size)| start + size <= range.start));
+
+        let mut start = range.start;
+        let mut end = range.end;
+
+        self.used -= end - start;
+        self.allocations -= 1;
+
+        if let Some((&prev_start, &prev_size)) = self.by_offset.range(..start).next_back() {
+            if prev_start + prev_size == start {
+                self.remove_free(prev_start, prev_size);
+                start = prev_start;
+            }
+        }
+
+        if let Some(&next_size) = self.by_offset.get(&end) {
+            self.remove_free(end, next_size);
+            end += next_size;
+        }
+
+        self.insert_free(start, end - start);
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allocations == 0
+    }
+
+    pub fn stats(&self) -> AllocatorStats {
+        AllocatorStats {
+            size: self.size,
+            used: self.used,
+            allocations: self.allocations,
+            free_blocks: self.by_offset.len(),
+            largest_free_block: self.by_size.iter().next_back().map_or(0, |&(size, _)| size)
+        }
+    }
+
+    fn insert_free(&mut self, start: usize, size: usize) {
+        self.by_offset.insert(start, size);
+        self.by_size.insert((size, start));
+    }
+
+    fn remove_free(&mut self, start: usize, size: usize) {
+        self.by_offset.remove(&start);
+        self.by_size.remove(&(size, start));
+    }
+}
+
+/// Bytes handed out by a `RingRanges` during one frame, released all at once by `release`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RingFrame {
+    end: usize,
+    size: usize
+}
+
+/// Bookkeeping for a ring of `size` bytes that is allocated from frame by frame and released in
+/// the same order, oldest frame first.
+#[derive(Debug, Clone)]
+pub struct RingRanges {
+    size: usize,
+    head: usize,
+    tail: usize,
+    used: usize,
+    frame_used: usize
+}
+
+impl RingRanges {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            head: 0,
+            tail: 0,
+            used: 0,
+            frame_used: 0
+        }
+    }
+
+    /// Finds `size` bytes starting at a multiple of `align`, which has to be a power of two.
+    /// Allocations that don't fit before the end of the ring wrap around to its start.
+    pub fn alloc(&mut self, size: usize, align: usize) -> Option<Range<usize>> {
+        debug_assert!(align.is_power_of_two());
+        let size = size.max(1);
+
+        if self.used == 0 {
+            self.head = 0;
+            self.tail = 0;
+        }
+
+        let aligned = self.head.checked_add(align - 1)? & !(align - 1);
+        let start = if self.head >= self.tail && (self.used == 0 || self.head != self.tail) {
+            if aligned.checked_add(size)? <= self.size {
+                aligned
+            } else if size <= self.tail {
+                0
+            } else {
+                return None;
+            }
+        } else if aligned.checked_add(size)? <= self.tail {
+            aligned
+        } else {
+            return None;
+        };
+
+        let end = start + size;
+        let consumed = if start >= self.head {
+            end - self.head
+        } else {
+            self.size - self.head + end
+        };
+
+        self.head = end;
+        self.used += consumed;
+        self.frame_used += consumed;
+
+        Some(start..end)
+    }
+
+    /// Closes the current frame, returning what has to be passed to `release` once the GPU is
+    /// done with it.
+    pub fn end_frame(&mut self) -> RingFrame {
+        let frame = RingFrame {
+            end: self.head,
+            size: self.frame_used
+        };
+        self.frame_used = 0;
+        frame
+    }
+
+    /// Makes the space of the oldest unreleased frame available again.
+    pub fn release(&mut self, frame: RingFrame) {
+        // empty frames may predate the ring rewinding to its start once it ran empty
+        if frame.size != 0 {
+            self.tail = frame.end;
+            self.used -= frame.size;
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Offset the next allocation is placed at, unless it needs to wrap around.
+    pub fn head(&self) -> usize {
+        self.head
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_picks_smallest_fitting_block() {
+        let mut ranges = RangeAllocator::new(1024);
+        let a = ranges.alloc(100, 1).unwrap();
+        let _b = ranges.alloc(50, 1).unwrap();
+        let c = ranges.alloc(200, 1).unwrap();
+        let _d = ranges.alloc(10, 1).unwrap();
+
+        ranges.free(a);
+        ranges.free(c);
+
+        assert_eq!(ranges.alloc(40, 1), Some(0..40));
+        assert_eq!(ranges.alloc(150, 1), Some(150..300));
+        assert_eq!(ranges.alloc(600, 1), Some(360..960));
+    }
+
+    #[test]
+    fn range_coalesces_freed_neighbours() {
+        let mut ranges = RangeAllocator::new(300);
+        let a = ranges.alloc(100, 1).unwrap();
+        let b = ranges.alloc(100, 1).unwrap();
+        let c = ranges.alloc(100, 1).unwrap();
+
+        ranges.free(b);
+        ranges.free(a);
+        assert_eq!(ranges.stats().free_blocks, 1);
+        assert_eq!(ranges.stats().largest_free_block, 200);
+
+        ranges.free(c);
+        assert!(ranges.is_empty());
+        assert_eq!(ranges.stats(), AllocatorStats {
+            size: 300,
+            used: 0,
+            allocations: 0,
+            free_blocks: 1,
+            largest_free_block: 300
+        });
+        assert_eq!(ranges.alloc(300, 1), Some(0..300));
+    }
+
+    #[test]
+    fn range_keeps_alignment_padding_free() {
+        let mut ranges = RangeAllocator::new(256);
+        assert_eq!(ranges.alloc(10, 1), Some(0..10));
+        assert_eq!(ranges.alloc(16, 64), Some(64..80));
+
+        let stats = ranges.stats();
+        assert_eq!(stats.used, 26);
+        assert_eq!(stats.free_blocks, 2);
+
+        assert_eq!(ranges.alloc(54, 1), Some(10..64));
+        assert_eq!(ranges.stats().free_blocks, 1);
+    }
+
+    #[test]
+    fn range_exhaustion() {
+        let mut ranges = RangeAllocator::new(64);
+        assert_eq!(ranges.alloc(65, 1), None);
+        assert_eq!(ranges.alloc(1, 1), Some(0..1));
+        assert_eq!(ranges.alloc(1, 64), None);
+        assert_eq!(ranges.alloc(63, 1), Some(1..64));
+        assert_eq!(ranges.alloc(1, 1), None);
+        assert_eq!(ranges.stats().free(), 0);
+
+        assert_eq!(RangeAllocator::new(0).alloc(1, 1), None);
+    }
+
+    #[test]
+    fn ring_wraps_around_to_released_space() {
+        let mut ring = RingRanges::new(256);
+        assert_eq!(ring.alloc(100, 1), Some(0..100));
+        let first = ring.end_frame();
+        assert_eq!(ring.alloc(100, 1), Some(100..200));
+        let second = ring.end_frame();
+
+        // nothing is free before the end of the ring and nothing was released at its start
+        assert_eq!(ring.alloc(100, 1), None);
+
+        ring.release(first);
+        assert_eq!(ring.used(), 100);
+        assert_eq!(ring.alloc(100, 1), Some(0..100));
+        // the skipped tail of the ring counts as used until the frame is released
+        assert_eq!(ring.used(), 256);
+        let third = ring.end_frame();
+
+        ring.release(second);
+        assert_eq!(ring.used(), 156);
+        ring.release(third);
+        assert_eq!(ring.used(), 0);
+    }
+
+    #[test]
+    fn ring_releases_oldest_frame_first() {
+        let mut ring = RingRanges::new(300);
+        ring.alloc(100, 1).unwrap();
+        let first = ring.end_frame();
+        ring.alloc(100, 1).unwrap();
+        let second = ring.end_frame();
+        ring.alloc(50, 1).unwrap();
+
+        ring.release(first);
+        assert_eq!(ring.used(), 150);
+        // only the first frame's space is free, the second's is still in use
+        assert_eq!(ring.alloc(150, 1), None);
+        assert_eq!(ring.alloc(100, 1), Some(0..100));
+
+        // the space skipped at the end of the ring stays used until the open frame is released
+        ring.release(second);
+        assert_eq!(ring.used(), 200);
+    }
+
+    #[test]
+    fn ring_full_and_empty() {
+        let mut ring = RingRanges::new(256);
+        assert_eq!(ring.alloc(256, 1), Some(0..256));
+        assert_eq!(ring.alloc(1, 1), None);
+        let full = ring.end_frame();
+
+        // an empty frame releases nothing
+        let empty = ring.end_frame();
+        ring.release(full);
+        ring.release(empty);
+        assert_eq!(ring.used(), 0);
+
+        // once empty the ring starts over at offset 0
+        assert_eq!(ring.alloc(16, 64), Some(0..16));
+        assert_eq!(ring.alloc(16, 64), Some(64..80));
+        assert_eq!(ring.alloc(0, 1), Some(80..81));
+        assert_eq!(ring.head(), 81);
+    }
+}
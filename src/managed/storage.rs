@@ -0,0 +1,216 @@
+use std::alloc::Layout;
+use std::sync::Arc;
+
+/// Source of the host memory managed objects are backed by, like pool storage, command buffer
+/// control memory and queue memory.
+///
+/// # Safety
+///
+/// Implementations must return memory aligned to at least `align`, or null on failure, and
+/// accept back anything they handed out through `free`.
+pub unsafe trait HostAllocator: Send + Sync {
+    fn alloc(&self, size: usize, align: usize) -> *mut u8;
+
+    /// # Safety
+    ///
+    /// `memory` has to come from `alloc` of the same allocator with the same `size` and `align`,
+    /// and must not be used afterwards.
+    unsafe fn free(&self, memory: *mut u8, size: usize, align: usize);
+}
+
+/// Allocates through `memalign` and `free`, the default of every managed builder.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct LibcAllocator;
+
+unsafe impl HostAllocator for LibcAllocator {
+    fn alloc(&self, size: usize, align: usize) -> *mut u8 {
+        unsafe {
+            libc::memalign(align, size) as *mut u8
+        }
+    }
+
+    unsafe fn free(&self, memory: *mut u8, _: usize, _: usize) {
+        libc::free(memory as *mut libc::c_void);
+    }
+}
+
+/// Allocates through the Rust global allocator.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct GlobalAllocator;
+
+unsafe impl HostAllocator for GlobalAllocator {
+    fn alloc(&self, size: usize, align: usize) -> *mut u8 {
+        match Layout::from_size_align(size.max(1), align) {
+            Ok(layout) => unsafe {
+                std::alloc::alloc(layout)
+            },
+            Err(_) => 0 as _
+        }
+    }
+
+    unsafe fn free(&self, memory: *mut u8, size: usize, align: usize) {
+        std::alloc::dealloc(memory, Layout::from_size_align_unchecked(size.max(1), align));
+    }
+}
+
+pub fn default_allocator() -> Arc<dyn HostAllocator> {
+    Arc::new(LibcAllocator)
+}
+
+/// A block of host memory along with what frees it, if anything.
+pub struct AlignedStorage {
+    memory: *mut u8,
+    size: usize,
+    align: usize,
+    allocator: Option<Arc<dyn HostAllocator>>
+}
+
+impl AlignedStorage {
+    /// Allocates `size` bytes aligned to `align` from `allocator`, which frees them again on drop.
+    pub fn alloc(allocator: Arc<dyn HostAllocator>, size: usize, align: usize) -> Option<Self> {
+        if !align.is_power_of_two() {
+            return None;
+        }
+
+        let memory = allocator.alloc(size, align);
+        if memory.is_null() {
+            None
+        } else {
+            Some(Self {
+                memory,
+                size,
+                align,
+                allocator: Some(allocator)
+            })
+        }
+    }
+
+    /// Takes ownership of memory obtained from `allocator`.
+    ///
+    /// # Safety
+    ///
+    /// `memory` has to be the result of `allocator.alloc(size, align)` and must not be freed by
+    /// anything else.
+    pub unsafe fn from_raw(memory: *mut u8, size: usize, align: usize, allocator: Arc<dyn HostAllocator>) -> Self {
+        Self {
+            memory,
+            size,
+            align,
+            allocator: Some(allocator)
+        }
+    }
+
+    /// Memory owned elsewhere, which is never freed through this.
+    ///
+    /// # Safety
+    ///
+    /// `memory` has to point to `size` bytes that outlive every object using it.
+    pub unsafe fn borrowed(memory: *mut u8, size: usize) -> Self {
+        Self {
+            memory,
+            size,
+            align: 1,
+            allocator: None
+        }
+    }
+
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.memory
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
+    /// Whether the memory is freed when this is dropped.
+    pub fn is_owned(&self) -> bool {
+        self.allocator.is_some()
+    }
+}
+
+impl Drop for AlignedStorage {
+    fn drop(&mut self) {
+        if let Some(allocator) = self.allocator.as_ref() {
+            unsafe {
+                allocator.free(self.memory, self.size, self.align);
+            }
+        }
+    }
+}
+
+unsafe impl Send for AlignedStorage {}
+unsafe impl Sync for AlignedStorage {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingAllocator {
+        allocs: AtomicUsize,
+        frees: AtomicUsize
+    }
+
+    unsafe impl HostAllocator for CountingAllocator {
+        fn alloc(&self, size: usize, align: usize) -> *mut u8 {
+            self.allocs.fetch_add(1, Ordering::Relaxed);
+            GlobalAllocator.alloc(size, align)
+        }
+
+        unsafe fn free(&self, memory: *mut u8, size: usize, align: usize) {
+            self.frees.fetch_add(1, Ordering::Relaxed);
+            GlobalAllocator.free(memory, size, align)
+        }
+    }
+
+    #[test]
+    fn alloc_is_aligned() {
+        for &align in &[1, 16, 0x1000] {
+            let storage = AlignedStorage::alloc(default_allocator(), 0x100, align).unwrap();
+            assert_eq!(storage.as_ptr() as usize % align, 0);
+            assert_eq!(storage.size(), 0x100);
+            assert_eq!(storage.align(), align);
+            assert!(storage.is_owned());
+        }
+    }
+
+    #[test]
+    fn alloc_rejects_invalid_alignment() {
+        let allocator = Arc::new(CountingAllocator::default());
+        assert!(AlignedStorage::alloc(allocator.clone(), 0x100, 0).is_none());
+        assert!(AlignedStorage::alloc(allocator.clone(), 0x100, 48).is_none());
+        assert_eq!(allocator.allocs.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn owned_storage_is_freed_once() {
+        let allocator = Arc::new(CountingAllocator::default());
+        let storage = AlignedStorage::alloc(allocator.clone(), 0x100, 0x100).unwrap();
+        assert_eq!(allocator.frees.load(Ordering::Relaxed), 0);
+
+        drop(storage);
+        assert_eq!(allocator.allocs.load(Ordering::Relaxed), 1);
+        assert_eq!(allocator.frees.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn borrowed_storage_is_not_freed() {
+        let allocator = Arc::new(CountingAllocator::default());
+        let owner = AlignedStorage::alloc(allocator.clone(), 0x100, 0x100).unwrap();
+
+        let borrowed = unsafe {
+            AlignedStorage::borrowed(owner.as_ptr(), owner.size())
+        };
+        assert!(!borrowed.is_owned());
+        drop(borrowed);
+        assert_eq!(allocator.frees.load(Ordering::Relaxed), 0);
+
+        drop(owner);
+        assert_eq!(allocator.frees.load(Ordering::Relaxed), 1);
+    }
+}